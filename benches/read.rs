@@ -0,0 +1,64 @@
+// 读取性能测试: cargo bench
+use std::path::PathBuf;
+use criterion::{criterion_group, criterion_main, Criterion};
+use xlsx_batch_reader::{read::XlsxBook, MAX_COL_NUM};
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+use common::{istr, make_xlsx_with};
+
+const NROWS: u32 = 20_000;
+
+/// build a xlsx file of NROWS rows: number, shared string, inline string, number
+fn make_bench_xlsx() -> PathBuf {
+    let mut sheet = String::from("<worksheet><sheetData>");
+    for r in 1..=NROWS {
+        sheet += &format!(r#"<row r="{r}"><c r="A{r}"><v>{r}</v></c><c r="B{r}" t="s"><v>{}</v></c>{}<c r="D{r}"><v>{}.25</v></c></row>"#, r % 100, istr(&format!("C{r}"), &format!("name{r}")), r * 3);
+    }
+    sheet += "</sheetData></worksheet>";
+    let sst: String = (0..100).map(|i| format!("<si><t>shared{i}</t></si>")).collect();
+    make_xlsx_with("bench", &sheet, &[("xl/sharedStrings.xml", &format!(r#"<sst count="100" uniqueCount="100">{sst}</sst>"#))])
+}
+
+fn bench_read(c: &mut Criterion) {
+    let path = make_bench_xlsx();
+    let sheet_name = "Sheet1".to_string();
+    let mut book = XlsxBook::new(&path, true).unwrap();
+
+    c.bench_function("iterator", |b| b.iter(|| {
+        let sheet = book.get_sheet_by_name(&sheet_name, 1000, 0, 1, MAX_COL_NUM, false).unwrap();
+        let mut n = 0;
+        for batch in sheet {
+            let (_, rows) = batch.unwrap();
+            n += rows.len();
+        }
+        assert_eq!(n, NROWS as usize);
+    }));
+
+    c.bench_function("for_each_row", |b| b.iter(|| {
+        let mut sheet = book.get_sheet_by_name(&sheet_name, 1000, 0, 1, MAX_COL_NUM, false).unwrap();
+        let mut n = 0;
+        sheet.for_each_row(|_, _| {
+            n += 1;
+            Ok(())
+        }).unwrap();
+        assert_eq!(n, NROWS as usize);
+    }));
+
+    c.bench_function("parallel", |b| b.iter(|| {
+        let mut sheet = book.get_sheet_by_name(&sheet_name, 1000, 0, 1, MAX_COL_NUM, false).unwrap();
+        sheet.with_parallel(0).unwrap();
+        let mut n = 0;
+        sheet.for_each_row(|_, _| {
+            n += 1;
+            Ok(())
+        }).unwrap();
+        assert_eq!(n, NROWS as usize);
+    }));
+
+    drop(book);
+    std::fs::remove_file(path).unwrap();
+}
+
+criterion_group!(benches, bench_read);
+criterion_main!(benches);
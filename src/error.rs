@@ -0,0 +1,195 @@
+use std::fmt;
+
+use crate::{CellValue, ColNum, RowNum};
+
+/// Result type of this crate
+pub type Result<T> = std::result::Result<T, XlsxError>;
+
+/// Error type of this crate
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum XlsxError {
+    /// io error
+    Io(std::io::Error),
+    /// zip archive error
+    Zip(zip::result::ZipError),
+    /// required part (file in the zip archive) not found, such as `xl/workbook.xml`
+    MissingPart(String),
+    /// xml is broken or not as expected
+    MalformedXml(String),
+    /// vbaProject.bin is broken or not as expected
+    MalformedVba(String),
+    /// required attribute not found
+    MissingAttribute(String),
+    /// sheet not found by name
+    SheetNotFound(String),
+    /// invalid cell address, such as `1A`
+    InvalidCellRef(String),
+    /// the cell value can not be converted into the target type, the location is filled if known
+    Conversion {
+        sheet: Option<String>,
+        row: Option<RowNum>,
+        col: Option<ColNum>,
+        value: String,
+        target: &'static str,
+    },
+    /// header row check failed
+    HeaderCheckFailed {
+        sheet: String,
+        row: RowNum,
+        expected: String,
+    },
+    /// no header row found
+    NoHeaderRow(String),
+    /// column name not found
+    ColumnNotFound(String),
+    /// function called at a wrong time, such as calling with_* functions after reading
+    InvalidState(String),
+    /// resource limit set by ReadLimits exceeded
+    LimitExceeded(String),
+    /// reading cancelled by CancelToken
+    Cancelled,
+    /// error from rust_xlsxwriter
+    #[cfg(feature = "xlsxwriter")]
+    Writer(rust_xlsxwriter::XlsxError),
+}
+
+impl XlsxError {
+    /// conversion error without location
+    pub(crate) fn conversion(val: &CellValue<'_>, target: &'static str) -> Self {
+        XlsxError::Conversion { sheet: None, row: None, col: None, value: format!("{:?}", val), target }
+    }
+    /// xml error of the part, the limit error raised by reader is kept
+    pub(crate) fn from_xml(part: &str, e: quick_xml::Error) -> Self {
+        match LimitError::find(&e) {
+            Some(msg) => XlsxError::LimitExceeded(msg),
+            None => XlsxError::MalformedXml(format!("{} is broken: {:?}", part, e))
+        }
+    }
+    /// set location of the conversion error, other errors will be returned unchanged.
+    /// e.g. `cel.get::<f64>().map_err(|e| e.with_location(sheet.sheet_name(), row, col))`
+    pub fn with_location(self, sheet: &str, row: RowNum, col: ColNum) -> Self {
+        match self {
+            XlsxError::Conversion { value, target, .. } => {
+                XlsxError::Conversion { sheet: Some(sheet.to_string()), row: Some(row), col: Some(col), value, target }
+            },
+            e => e
+        }
+    }
+}
+
+impl fmt::Display for XlsxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XlsxError::Io(e) => write!(f, "io error: {}", e),
+            XlsxError::Zip(e) => write!(f, "zip error: {}", e),
+            XlsxError::MissingPart(p) => write!(f, "{} not found", p),
+            XlsxError::MalformedXml(s) => write!(f, "malformed xml: {}", s),
+            XlsxError::MalformedVba(s) => write!(f, "malformed vba project: {}", s),
+            XlsxError::MissingAttribute(a) => write!(f, "attribute {} not exist", a),
+            XlsxError::SheetNotFound(s) => write!(f, "{} sheet not found", s),
+            XlsxError::InvalidCellRef(s) => write!(f, "invalid cell address: {}", s),
+            XlsxError::Conversion { sheet, row, col, value, target } => {
+                write!(f, "invalid value-{} for {}", value, target)?;
+                if let (Some(row), Some(col)) = (row, col) {
+                    write!(f, " at {}!{}", sheet.as_deref().unwrap_or(""), crate::get_ord_from_tuple(*row, *col).unwrap_or_default())?;
+                }
+                Ok(())
+            },
+            XlsxError::HeaderCheckFailed { sheet, row, expected } => write!(f, "header row check failed at {}!{}: {}", sheet, row, expected),
+            XlsxError::NoHeaderRow(s) => write!(f, "no header row in sheet {}", s),
+            XlsxError::ColumnNotFound(c) => write!(f, "column name {} not found", c),
+            XlsxError::InvalidState(s) => write!(f, "{}", s),
+            XlsxError::LimitExceeded(s) => write!(f, "limit exceeded: {}", s),
+            XlsxError::Cancelled => write!(f, "reading cancelled"),
+            #[cfg(feature = "xlsxwriter")]
+            XlsxError::Writer(e) => write!(f, "writer error: {}", e),
+        }
+    }
+}
+
+/// io error payload raised by the reader when a limit is exceeded
+#[derive(Debug)]
+pub(crate) struct LimitError(pub(crate) String);
+
+impl LimitError {
+    /// find the limit error wrapped by xml error
+    fn find(e: &quick_xml::Error) -> Option<String> {
+        match e {
+            quick_xml::Error::Io(e) => e.get_ref().and_then(|e| e.downcast_ref::<LimitError>()).map(|e| e.0.clone()),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+impl std::error::Error for XlsxError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            XlsxError::Io(e) => Some(e),
+            XlsxError::Zip(e) => Some(e),
+            #[cfg(feature = "xlsxwriter")]
+            XlsxError::Writer(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<std::io::Error> for XlsxError {
+    fn from(e: std::io::Error) -> Self {
+        XlsxError::Io(e)
+    }
+}
+
+impl From<zip::result::ZipError> for XlsxError {
+    fn from(e: zip::result::ZipError) -> Self {
+        XlsxError::Zip(e)
+    }
+}
+
+impl From<quick_xml::Error> for XlsxError {
+    fn from(e: quick_xml::Error) -> Self {
+        match LimitError::find(&e) {
+            Some(msg) => XlsxError::LimitExceeded(msg),
+            None => XlsxError::MalformedXml(e.to_string())
+        }
+    }
+}
+
+impl From<quick_xml::events::attributes::AttrError> for XlsxError {
+    fn from(e: quick_xml::events::attributes::AttrError) -> Self {
+        XlsxError::MalformedXml(e.to_string())
+    }
+}
+
+impl From<std::num::ParseIntError> for XlsxError {
+    fn from(e: std::num::ParseIntError) -> Self {
+        XlsxError::MalformedXml(e.to_string())
+    }
+}
+
+impl From<std::num::ParseFloatError> for XlsxError {
+    fn from(e: std::num::ParseFloatError) -> Self {
+        XlsxError::MalformedXml(e.to_string())
+    }
+}
+
+impl From<std::string::FromUtf8Error> for XlsxError {
+    fn from(e: std::string::FromUtf8Error) -> Self {
+        XlsxError::MalformedXml(e.to_string())
+    }
+}
+
+#[cfg(feature = "xlsxwriter")]
+impl From<rust_xlsxwriter::XlsxError> for XlsxError {
+    fn from(e: rust_xlsxwriter::XlsxError) -> Self {
+        XlsxError::Writer(e)
+    }
+}
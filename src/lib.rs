@@ -1,21 +1,30 @@
 //! An Excel/OpenDocument Spreadsheets file batch reader, in pure Rust. This crate supports Office 2007 or newer file formats(xlsx, xlsm, etc). The most obvious difference from other Excel file reading crates is that it does not read the whole file into memory, but read in batches. So that it can maintain low memory usage, especially when reading large files.
+use std::ops::Deref;
 use chrono::Local;
-use anyhow::{anyhow, Result};
 use lazy_static::lazy_static;
 use read::FromCellValue;
 
+/// Error types
+pub mod error;
 /// Excel file reader
 pub mod read;
 /// Excel file writer
 #[cfg(feature = "xlsxwriter")]
 pub mod write;
+/// Async stream of batches, running on tokio
+#[cfg(feature = "tokio")]
+pub mod stream;
 
 
+pub use error::{Result, XlsxError};
+
 /// reexport
 pub use zip;
 pub use chrono;
 #[cfg(feature = "xlsxwriter")]
 pub use rust_xlsxwriter;
+#[cfg(feature = "tokio")]
+pub use futures_core;
 
 /// days since UNIX epoch
 pub type Date32 = i32;  
@@ -118,7 +127,7 @@ pub fn get_tuple_from_ord(addr: &[u8]) -> Result<(RowNum, ColNum)> {
         i -= 1;
         if addr[i] > b'@' {
             if row.is_none() {
-                row = Some(String::from_utf8(addr[i+1..].to_vec())?.parse::<RowNum>()?);
+                row = Some(String::from_utf8_lossy(&addr[i+1..]).parse::<RowNum>().map_err(|_| XlsxError::InvalidCellRef(String::from_utf8_lossy(&addr).to_string()))?);
             };
             col += ((addr[i] - b'@') as ColNum) * j;
             j *= 26;
@@ -127,7 +136,7 @@ pub fn get_tuple_from_ord(addr: &[u8]) -> Result<(RowNum, ColNum)> {
     if let Some(row) = row {
         Ok((row, col))
     } else {
-        return Err(anyhow!("invalid cell address: {:?}", addr))
+        return Err(XlsxError::InvalidCellRef(String::from_utf8_lossy(&addr).to_string()))
     }
 }
 
@@ -141,7 +150,8 @@ pub fn get_ord_from_tuple(row: RowNum, col: ColNum) -> Result<String> {
     }
 }
 
-/// check whether the cell is a merged cell. If it is the first cell in the merged area, return the size of the merged area. RowNum and ColNum start from 1.
+/// check whether the cell is a merged cell. If it is the first cell in the merged area, return the size of the merged area. RowNum and ColNum start from 1.   
+/// this function scans all merged ranges, use MergedRanges::lookup for many cells
 pub fn is_merged_cell(mgs: &Vec<MergedRange>, row: RowNum, col: ColNum) -> (bool, Option<(RowNum, ColNum)>) {
     for (left_top, right_end) in mgs {
         if left_top.0 <= row && left_top.1 <= col && right_end.0 >= row && right_end.1 >= col {
@@ -155,6 +165,89 @@ pub fn is_merged_cell(mgs: &Vec<MergedRange>, row: RowNum, col: ColNum) -> (bool
     return (false, None)
 }
 
+/// merged ranges with a row band index, so that the owning range of a cell can be found in O(log n)
+#[derive(Debug, Clone, Default)]
+pub struct MergedRanges {
+    ranges: Vec<MergedRange>,
+    //  合并区域上下边界去重排序后的行号，相邻两个行号构成一个行区间
+    bounds: Vec<RowNum>,
+    //  行区间的线段树，节点 -> 完全覆盖该节点行区间的合并区域(左列, 右列, 序号)，按左列排序
+    nodes: Vec<Vec<(ColNum, ColNum, usize)>>,
+}
+
+impl MergedRanges {
+    /// build index for merged ranges, the ranges should not overlap each other
+    pub fn new(ranges: Vec<MergedRange>) -> Self {
+        let mut bounds: Vec<RowNum> = ranges.iter().flat_map(|(lt, rb)| [lt.0, rb.0.saturating_add(1)]).collect();
+        bounds.sort_unstable();
+        bounds.dedup();
+        //  每个合并区域只登记到O(log n)个节点，同一节点的区域覆盖相同的行，列互不重叠
+        let size = bounds.len().saturating_sub(1).next_power_of_two();
+        let mut nodes = vec![Vec::new(); 2*size];
+        let band = |row: RowNum| bounds.binary_search(&row).unwrap_or_else(|v| v);
+        for (i, (lt, rb)) in ranges.iter().enumerate() {
+            let (mut l, mut r) = (band(lt.0) + size, band(rb.0.saturating_add(1)) + size);
+            while l < r {
+                if l & 1 == 1 {
+                    nodes[l].push((lt.1, rb.1, i));
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    nodes[r].push((lt.1, rb.1, i));
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+        }
+        for node in nodes.iter_mut() {
+            node.sort_unstable();
+        }
+        Self { ranges, bounds, nodes }
+    }
+    /// nodes of the segment tree whose row band covers the row, from the leaf to the root
+    fn nodes_of_row(&self, row: RowNum) -> impl Iterator<Item = &Vec<(ColNum, ColNum, usize)>> {
+        let pos = self.bounds.partition_point(|b| *b <= row);
+        let leaf = pos.checked_sub(1).filter(|v| *v + 1 < self.bounds.len()).map(|v| v + self.nodes.len()/2);
+        std::iter::successors(leaf, |v| Some(v >> 1).filter(|v| *v > 0)).map(|v| &self.nodes[v])
+    }
+    /// find the merged range containing the cell, returns the range, whether the cell is the top left cell, and the size of the range. RowNum and ColNum start from 1.
+    pub fn lookup(&self, row: RowNum, col: ColNum) -> Option<(&MergedRange, bool, (RowNum, ColNum))> {
+        let i = self.nodes_of_row(row).find_map(|node| {
+            let pos = node.partition_point(|(left, _, _)| *left <= col);
+            node.get(pos.checked_sub(1)?).filter(|(_, right, _)| *right >= col).map(|(_, _, i)| *i)
+        })?;
+        let rng = &self.ranges[i];
+        let (left_top, right_end) = rng;
+        Some((rng, *left_top == (row, col), (right_end.0-left_top.0+1, right_end.1-left_top.1+1)))
+    }
+    /// check whether the cell is a merged cell, the same as is_merged_cell
+    pub fn is_merged_cell(&self, row: RowNum, col: ColNum) -> (bool, Option<(RowNum, ColNum)>) {
+        match self.lookup(row, col) {
+            Some((_, true, span)) => (true, Some(span)),
+            Some((_, false, _)) => (true, None),
+            None => (false, None)
+        }
+    }
+    /// get merged ranges covering the row
+    pub fn ranges_in_row(&self, row: RowNum) -> impl Iterator<Item = &MergedRange> {
+        self.nodes_of_row(row).flat_map(|node| node.iter().map(|(_, _, i)| &self.ranges[*i]))
+    }
+}
+
+impl Deref for MergedRanges {
+    type Target = Vec<MergedRange>;
+    fn deref(&self) -> &Self::Target {
+        &self.ranges
+    }
+}
+
+impl From<Vec<MergedRange>> for MergedRanges {
+    fn from(ranges: Vec<MergedRange>) -> Self {
+        Self::new(ranges)
+    }
+}
+
 /// Cell Value Type
 #[derive(Debug, Clone)]
 pub enum CellValue<'a> {
@@ -174,6 +267,20 @@ impl<'a> CellValue<'a> {
     pub fn get<T: FromCellValue>(&'a self) -> Result<Option<T>> {
         T::try_from_cval(self)
     }
+    /// convert into an owned value, shared string will be converted into String
+    pub fn into_owned(self) -> CellValue<'static> {
+        match self {
+            CellValue::Blank => CellValue::Blank,
+            CellValue::Bool(v) => CellValue::Bool(v),
+            CellValue::Number(v) => CellValue::Number(v),
+            CellValue::Date(v) => CellValue::Date(v),
+            CellValue::Time(v) => CellValue::Time(v),
+            CellValue::Datetime(v) => CellValue::Datetime(v),
+            CellValue::Shared(v) => CellValue::String(v.clone()),
+            CellValue::String(v) => CellValue::String(v),
+            CellValue::Error(v) => CellValue::Error(v),
+        }
+    }
 }
 
 
@@ -0,0 +1,149 @@
+use crate::{error::Result, CellValue, ColNum, MergedRange, RowNum};
+use super::ValidationOperator;
+
+/// differential format (dxf) in styles.xml, which is applied by conditional formatting
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DxfStyle {
+    /// font color, e.g. `FF9C0006`, or `theme:1` if it refers to the theme
+    pub font_color: Option<String>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strike: bool,
+    /// fill color, e.g. `FFFFC7CE`, or `theme:1` if it refers to the theme
+    pub fill_color: Option<String>,
+    /// number format code
+    pub num_fmt: Option<String>,
+}
+
+/// type of conditional formatting rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CfRuleKind {
+    CellIs,
+    Expression,
+    ColorScale,
+    DataBar,
+    Top10,
+    DuplicateValues,
+    /// other types, e.g. containsText, aboveAverage, iconSet
+    Other(String),
+}
+
+/// conditional formatting rule
+#[derive(Debug, Clone)]
+pub struct CfRule {
+    pub kind: CfRuleKind,
+    pub priority: Option<u32>,
+    /// operator of cellIs
+    pub operator: Option<ValidationOperator>,
+    /// formulas of cellIs and expression, e.g. `100`, `"done"`, `$B1>$C1`
+    pub formulas: Vec<String>,
+    /// index of the dxf in styles.xml
+    pub dxf_id: Option<u32>,
+    /// differential format referred by dxf_id
+    pub dxf: Option<DxfStyle>,
+    /// colors of colorScale and dataBar
+    pub colors: Vec<String>,
+    /// rank of top10
+    pub rank: Option<u32>,
+    /// top10 by percent
+    pub percent: bool,
+    /// top10 from the bottom
+    pub bottom: bool,
+    pub stop_if_true: bool,
+}
+
+impl CfRule {
+    /// parse the attributes of cfRule
+    pub(super) fn from_attrs(e: &quick_xml::events::BytesStart<'_>) -> Result<Self> {
+        let attr_val = |tag: &str| -> Result<Option<String>> {
+            match e.try_get_attribute(tag)? {
+                Some(v) => Ok(Some(v.unescape_value()?.to_string())),
+                None => Ok(None)
+            }
+        };
+        let flag = |tag: &str| -> Result<bool> {
+            Ok(matches!(attr_val(tag)?.as_deref(), Some("1" | "true")))
+        };
+        let kind = match attr_val("type")?.as_deref() {
+            Some("cellIs") => CfRuleKind::CellIs,
+            Some("expression") => CfRuleKind::Expression,
+            Some("colorScale") => CfRuleKind::ColorScale,
+            Some("dataBar") => CfRuleKind::DataBar,
+            Some("top10") => CfRuleKind::Top10,
+            Some("duplicateValues") => CfRuleKind::DuplicateValues,
+            Some(v) => CfRuleKind::Other(v.to_string()),
+            None => CfRuleKind::Other(String::new())
+        };
+        Ok(Self {
+            kind,
+            priority: attr_val("priority")?.and_then(|v| v.parse().ok()),
+            operator: attr_val("operator")?.map(|v| ValidationOperator::from_attr(&v)),
+            formulas: Vec::new(),
+            dxf_id: attr_val("dxfId")?.and_then(|v| v.parse().ok()),
+            dxf: None,
+            colors: Vec::new(),
+            rank: attr_val("rank")?.and_then(|v| v.parse().ok()),
+            percent: flag("percent")?,
+            bottom: flag("bottom")?,
+            stop_if_true: flag("stopIfTrue")?,
+        })
+    }
+    /// evaluate a cellIs rule against the cell value, None if it can not be evaluated
+    /// (not a cellIs rule, blank cell, or the formulas refer to cells or functions)
+    pub fn evaluate(&self, val: &CellValue<'_>) -> Option<bool> {
+        if self.kind != CfRuleKind::CellIs {
+            return None;
+        }
+        let op = self.operator.unwrap_or(ValidationOperator::Between);
+        let f1 = self.formulas.first()?.trim();
+        match val {
+            CellValue::Blank | CellValue::Error(_) => None,
+            CellValue::Number(n) | CellValue::Date(n) | CellValue::Time(n) | CellValue::Datetime(n) => {
+                let v1 = f1.parse::<f64>().ok()?;
+                let v2 = match self.formulas.get(1) {
+                    Some(f) => Some(f.trim().parse::<f64>().ok()?),
+                    None => None
+                };
+                Some(op.compare(*n, v1, v2))
+            },
+            _ => {
+                //  文本只支持与字符串常量比较是否相等
+                let text = f1.strip_prefix('"')?.strip_suffix('"')?.replace("\"\"", "\"");
+                let s = val.get::<String>().ok()??;
+                match op {
+                    ValidationOperator::Equal => Some(s == text),
+                    ValidationOperator::NotEqual => Some(s != text),
+                    _ => None
+                }
+            }
+        }
+    }
+}
+
+/// conditional formatting of cell ranges
+#[derive(Debug, Clone)]
+pub struct ConditionalFormat {
+    /// ranges, e.g. `A1:A10 C1`
+    pub sqref: String,
+    pub ranges: Vec<MergedRange>,
+    pub rules: Vec<CfRule>,
+}
+
+impl ConditionalFormat {
+    /// check whether the cell is covered by the ranges
+    pub fn contains(&self, row: RowNum, col: ColNum) -> bool {
+        self.ranges.iter().any(|((r1, c1), (r2, c2))| row >= *r1 && row <= *r2 && col >= *c1 && col <= *c2)
+    }
+}
+
+/// get color from the attributes rgb or theme
+pub(super) fn get_color_attr(e: &quick_xml::events::BytesStart<'_>) -> Result<Option<String>> {
+    if let Some(v) = e.try_get_attribute("rgb")? {
+        Ok(Some(v.unescape_value()?.to_string()))
+    } else if let Some(v) = e.try_get_attribute("theme")? {
+        Ok(Some(format!("theme:{}", v.unescape_value()?)))
+    } else {
+        Ok(None)
+    }
+}
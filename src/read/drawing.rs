@@ -0,0 +1,563 @@
+use std::{collections::HashMap, fs::File, io::{BufReader, Read}};
+use zip::ZipArchive;
+use quick_xml::{events::Event, reader::{NsReader, Reader}};
+use crate::{error::{Result, XlsxError}, get_tuple_from_ord, ColNum, MergedRange, RowNum};
+use super::{get_rel_attr, open_part, read_part_rels, resolve_part, ReadLimits, XlsxBook, MAX_RESERVED};
+
+/// content types declared in `[Content_Types].xml`
+#[derive(Debug, Default)]
+struct ContentTypes {
+    defaults: HashMap<String, String>,    //  按扩展名
+    overrides: HashMap<String, String>,    //  按part
+}
+
+impl ContentTypes {
+    fn read(zip_archive: &mut ZipArchive<BufReader<File>>, limits: &ReadLimits) -> Result<Self> {
+        let mut types = Self::default();
+        let file = match open_part(zip_archive, "[Content_Types].xml", limits) {
+            Ok(file) => file,
+            Err(XlsxError::MissingPart(_)) => return Ok(types),
+            Err(e) => return Err(e)
+        };
+        let mut buf = Vec::new();
+        let mut reader = Reader::from_reader(BufReader::new(file));
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                    match e.local_name().as_ref() {
+                        b"Default" => {
+                            types.defaults.insert(get_attr_val!(e, "Extension", to_string).to_ascii_lowercase(), get_attr_val!(e, "ContentType", to_string));
+                        },
+                        b"Override" => {
+                            types.overrides.insert(get_attr_val!(e, "PartName", to_string).trim_start_matches('/').to_string(), get_attr_val!(e, "ContentType", to_string));
+                        },
+                        _ => ()
+                    }
+                },
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(XlsxError::from_xml("[Content_Types].xml", e)),
+                _ => ()
+            }
+            buf.clear();
+        }
+        Ok(types)
+    }
+    /// get content type of the part
+    fn get(&self, part: &str) -> Option<String> {
+        if let Some(v) = self.overrides.get(part) {
+            return Some(v.clone());
+        }
+        let ext = part.rsplit_once('.')?.1.to_ascii_lowercase();
+        self.defaults.get(&ext).cloned()
+    }
+}
+
+/// how an image is anchored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnchorKind {
+    /// moves and resizes with the cells from..to
+    TwoCell,
+    /// moves with the cell from
+    OneCell,
+    /// placed by absolute position
+    Absolute,
+    /// in-cell picture of Excel 365 (richData)
+    InCell,
+}
+
+/// image in a sheet
+#[derive(Debug, Clone)]
+pub struct SheetImage {
+    /// part in the zip archive, e.g. `xl/media/image1.png`
+    pub part: String,
+    /// content type, e.g. `image/png`
+    pub content_type: Option<String>,
+    pub name: Option<String>,
+    /// alternative text
+    pub descr: Option<String>,
+    pub anchor: AnchorKind,
+    /// anchor cells (from, to), from == to for oneCell and inCell anchors, None for absolute anchors
+    pub range: Option<MergedRange>,
+    /// size in EMU (cx, cy) of oneCell and absolute anchors
+    pub size: Option<(i64, i64)>,
+}
+
+/// chart series
+#[derive(Debug, Clone, Default)]
+pub struct ChartSeries {
+    /// type of the chart which the series belongs to, e.g. `barChart`, `lineChart`, `pieChart`
+    pub chart_type: String,
+    /// cached name of the series
+    pub name: Option<String>,
+    /// reference of the name, e.g. `Sheet1!$B$1`
+    pub name_ref: Option<String>,
+    /// reference of categories (x values of scatter charts), e.g. `Sheet1!$A$2:$A$5`
+    pub categories_ref: Option<String>,
+    /// reference of values (y values of scatter charts), e.g. `Sheet1!$B$2:$B$5`
+    pub values_ref: Option<String>,
+    /// cached categories
+    pub categories: Vec<Option<String>>,
+    /// cached values
+    pub values: Vec<Option<f64>>,
+}
+
+/// chart in a sheet
+#[derive(Debug, Clone)]
+pub struct SheetChart {
+    /// part in the zip archive, e.g. `xl/charts/chart1.xml`
+    pub part: String,
+    pub name: Option<String>,
+    pub anchor: AnchorKind,
+    /// anchor cells (from, to), None for absolute anchors
+    pub range: Option<MergedRange>,
+    /// chart types in the plot area, more than one for combination charts
+    pub chart_types: Vec<String>,
+    pub title: Option<String>,
+    /// titles of axes
+    pub axis_titles: Vec<String>,
+    pub series: Vec<ChartSeries>,
+}
+
+/// anchor of the picture or chart being parsed
+struct Anchor {
+    kind: AnchorKind,
+    from: Option<(RowNum, ColNum)>,
+    to: Option<(RowNum, ColNum)>,
+    size: Option<(i64, i64)>,    //  EMU
+}
+
+impl Anchor {
+    fn new(kind: AnchorKind) -> Self {
+        Self { kind, from: None, to: None, size: None }
+    }
+    fn range(&self) -> Option<MergedRange> {
+        self.from.map(|f| (f, self.to.unwrap_or(f)))
+    }
+}
+
+/// parse drawing part into images and charts (not parsed yet), targets are resolved by the relationships of the drawing
+fn read_drawing(zip_archive: &mut ZipArchive<BufReader<File>>, drawing: &str, limits: &ReadLimits) -> Result<(Vec<SheetImage>, Vec<SheetChart>)> {
+    let rels = read_part_rels(zip_archive, drawing, limits)?;
+    let file = open_part(zip_archive, drawing, limits)?;
+    let mut reader = NsReader::from_reader(BufReader::new(file));
+    let mut buf = Vec::new();
+    let (mut images, mut charts) = (Vec::new(), Vec::new());
+    let mut anchor: Option<Anchor> = None;
+    let mut marker = 0;    //  1-from; 2-to
+    let mut text_tag = 0;    //  1-col; 2-row
+    let (mut row, mut col): (RowNum, ColNum) = (0, 0);
+    let mut pic: Option<(Option<String>, Option<String>, Option<String>)> = None;    //  name, descr, embed
+    let mut frame: Option<(Option<String>, Option<String>)> = None;    //  name, chart id
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                match e.local_name().as_ref() {
+                    b"twoCellAnchor" => anchor = Some(Anchor::new(AnchorKind::TwoCell)),
+                    b"oneCellAnchor" => anchor = Some(Anchor::new(AnchorKind::OneCell)),
+                    b"absoluteAnchor" => anchor = Some(Anchor::new(AnchorKind::Absolute)),
+                    b"from" => marker = 1,
+                    b"to" => marker = 2,
+                    b"col" if marker > 0 => text_tag = 1,
+                    b"row" if marker > 0 => text_tag = 2,
+                    b"ext" => {
+                        //  a:ext也出现在图片、图表属性中，只取anchor下的ext
+                        if let (Some(a), None, None) = (anchor.as_mut(), pic.as_ref(), frame.as_ref()) {
+                            if let (Some(cx), Some(cy)) = (e.try_get_attribute("cx")?, e.try_get_attribute("cy")?) {
+                                a.size = Some((cx.unescape_value()?.parse()?, cy.unescape_value()?.parse()?));
+                            }
+                        }
+                    },
+                    b"pic" => pic = Some((None, None, None)),
+                    b"graphicFrame" => frame = Some((None, None)),
+                    b"cNvPr" => {
+                        let name = e.try_get_attribute("name")?.map(|v| v.unescape_value().map(|v| v.to_string())).transpose()?;
+                        if let Some(p) = pic.as_mut() {
+                            p.0 = name;
+                            p.1 = e.try_get_attribute("descr")?.map(|v| v.unescape_value().map(|v| v.to_string())).transpose()?;
+                        } else if let Some(f) = frame.as_mut() {
+                            f.0 = name;
+                        }
+                    },
+                    b"blip" => {
+                        if let Some(p) = pic.as_mut() {
+                            p.2 = get_rel_attr(&reader, e, "embed")?;
+                        }
+                    },
+                    b"chart" => {
+                        if let Some(f) = frame.as_mut() {
+                            f.1 = get_rel_attr(&reader, e, "id")?;
+                        }
+                    },
+                    _ => ()
+                }
+            },
+            Ok(Event::Text(ref t)) if text_tag > 0 => {
+                let text = t.unescape()?;
+                //  drawing中行列从0开始
+                if text_tag == 1 {col = text.trim().parse::<ColNum>()? + 1} else {row = text.trim().parse::<RowNum>()? + 1}
+            },
+            Ok(Event::End(ref e)) => {
+                match e.local_name().as_ref() {
+                    b"col" | b"row" => text_tag = 0,
+                    b"from" | b"to" => {
+                        if let Some(a) = anchor.as_mut() {
+                            if marker == 1 {a.from = Some((row, col))} else {a.to = Some((row, col))}
+                        }
+                        marker = 0;
+                    },
+                    b"pic" => {
+                        if let (Some((name, descr, Some(embed))), Some(a)) = (pic.take(), anchor.as_ref()) {
+                            //  外部链接的图片不在压缩包中
+                            if let Some(rel) = rels.get(&embed).filter(|r| !r.external) {
+                                images.push(SheetImage {
+                                    part: resolve_part(drawing, &rel.target),
+                                    content_type: None,
+                                    name,
+                                    descr,
+                                    anchor: a.kind,
+                                    range: a.range(),
+                                    size: a.size,
+                                });
+                            }
+                        }
+                    },
+                    b"graphicFrame" => {
+                        if let (Some((name, Some(rid))), Some(a)) = (frame.take(), anchor.as_ref()) {
+                            if let Some(rel) = rels.get(&rid).filter(|r| r.is("chart") && !r.external) {
+                                charts.push(SheetChart {
+                                    part: resolve_part(drawing, &rel.target),
+                                    name,
+                                    anchor: a.kind,
+                                    range: a.range(),
+                                    chart_types: Vec::new(),
+                                    title: None,
+                                    axis_titles: Vec::new(),
+                                    series: Vec::new(),
+                                });
+                            }
+                        }
+                    },
+                    b"twoCellAnchor" | b"oneCellAnchor" | b"absoluteAnchor" => anchor = None,
+                    _ => ()
+                }
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(XlsxError::from_xml(drawing, e)),
+            _ => ()
+        }
+        buf.clear();
+    }
+    Ok((images, charts))
+}
+
+/// set cached point of the series, returns false if idx is not less than ptCount (or MAX_RESERVED)
+fn set_point<T: Clone>(points: &mut Vec<Option<T>>, count: Option<usize>, idx: usize, val: Option<T>) -> bool {
+    if idx >= std::cmp::min(count.unwrap_or(MAX_RESERVED), MAX_RESERVED) {
+        return false;
+    }
+    if points.len() <= idx {
+        points.resize(idx + 1, None);
+    }
+    points[idx] = val;
+    true
+}
+
+/// parse chart part into chart types, titles and series
+fn read_chart(zip_archive: &mut ZipArchive<BufReader<File>>, chart: &mut SheetChart, limits: &ReadLimits, warnings: &mut Vec<String>) -> Result<()> {
+    let file = open_part(zip_archive, &chart.part, limits)?;
+    let mut reader = Reader::from_reader(BufReader::new(file));
+    let mut buf = Vec::new();
+    let mut path: Vec<Vec<u8>> = Vec::new();    //  当前元素的路径
+    let mut chart_type = String::new();
+    let mut title: Option<String> = None;
+    let mut pt_idx = 0;
+    let (mut cat_count, mut val_count) = (None, None);    //  当前系列的ptCount
+    let in_path = |path: &[Vec<u8>], name: &[u8]| path.iter().any(|v| v == name);
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) => {
+                let name = e.local_name().as_ref().to_vec();
+                match name.as_slice() {
+                    v if v.ends_with(b"Chart") && path.last().map(|p| p.as_slice()) == Some(b"plotArea") => {
+                        chart_type = String::from_utf8_lossy(v).to_string();
+                        chart.chart_types.push(chart_type.clone());
+                    },
+                    b"ser" => {
+                        chart.series.push(ChartSeries { chart_type: chart_type.clone(), ..Default::default() });
+                        (cat_count, val_count) = (None, None);
+                    },
+                    b"title" => title = Some(String::new()),
+                    b"pt" => pt_idx = get_attr_val!(e, "idx", parse),
+                    _ => ()
+                }
+                path.push(name);
+            },
+            Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"ptCount" && in_path(&path, b"ser") => {
+                let cnt: usize = get_attr_val!(e, "val", parse);
+                if let Some(ser) = chart.series.last_mut() {
+                    if in_path(&path, b"cat") || in_path(&path, b"xVal") {
+                        ser.categories.resize(std::cmp::min(cnt, MAX_RESERVED), None);
+                        cat_count = Some(cnt);
+                    } else if in_path(&path, b"val") || in_path(&path, b"yVal") {
+                        ser.values.resize(std::cmp::min(cnt, MAX_RESERVED), None);
+                        val_count = Some(cnt);
+                    }
+                }
+            },
+            Ok(Event::Text(ref t)) if !path.is_empty() => {
+                let last = path[path.len()-1].as_slice();
+                if let Some(title) = title.as_mut().filter(|_| last == b"t" || last == b"v") {
+                    title.push_str(&t.unescape()?);
+                } else if let Some(ser) = chart.series.last_mut().filter(|_| in_path(&path, b"ser") && (last == b"f" || last == b"v")) {
+                    let text = t.unescape()?.to_string();
+                    let pt = last == b"v" && in_path(&path, b"pt");
+                    if in_path(&path, b"tx") {
+                        if last == b"f" {ser.name_ref = Some(text)} else {ser.name = Some(text)}
+                    } else if in_path(&path, b"cat") || in_path(&path, b"xVal") {
+                        if last == b"f" {
+                            ser.categories_ref = Some(text);
+                        } else if pt && !set_point(&mut ser.categories, cat_count, pt_idx, Some(text)) {
+                            warnings.push(format!("{}: category point {} is out of the point count", chart.part, pt_idx));
+                        }
+                    } else if in_path(&path, b"val") || in_path(&path, b"yVal") {
+                        if last == b"f" {
+                            ser.values_ref = Some(text);
+                        } else if pt && !set_point(&mut ser.values, val_count, pt_idx, text.trim().parse().ok()) {
+                            warnings.push(format!("{}: value point {} is out of the point count", chart.part, pt_idx));
+                        }
+                    }
+                }
+            },
+            Ok(Event::End(_)) => {
+                if let Some(name) = path.pop() {
+                    if name == b"title" {
+                        //  图表标题位于chart下，坐标轴标题位于catAx、valAx等下
+                        match (title.take(), path.last().map(|p| p.as_slice())) {
+                            (Some(t), Some(b"chart")) => chart.title = Some(t),
+                            (Some(t), Some(p)) if p.ends_with(b"Ax") => chart.axis_titles.push(t),
+                            _ => ()
+                        }
+                    }
+                }
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(XlsxError::from_xml(&chart.part, e)),
+            _ => ()
+        }
+        buf.clear();
+    }
+    Ok(())
+}
+
+/// read in-cell pictures (richData) of the book, returns the image parts by value metadata index (vm of cells, start from 1)
+fn read_rich_value_images(zip_archive: &mut ZipArchive<BufReader<File>>, limits: &ReadLimits) -> Result<HashMap<u32, String>> {
+    let mut images = HashMap::new();
+    //  valueMetadata(vm) -> futureMetadata(XLRICHVALUE) -> rich value -> LocalImageIdentifier -> richValueRel -> image
+    let file = match open_part(zip_archive, "xl/metadata.xml", limits) {
+        Ok(file) => file,
+        Err(XlsxError::MissingPart(_)) => return Ok(images),
+        Err(e) => return Err(e)
+    };
+    let (mut future_rvs, mut value_rcs): (Vec<usize>, Vec<usize>) = (Vec::new(), Vec::new());
+    {
+        let mut reader = Reader::from_reader(BufReader::new(file));
+        let mut buf = Vec::new();
+        let mut block = 0;    //  1-futureMetadata XLRICHVALUE; 2-valueMetadata
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                    match e.local_name().as_ref() {
+                        b"futureMetadata" => {
+                            block = if get_attr_val!(e, "name").as_ref() == "XLRICHVALUE" {1} else {0};
+                        },
+                        b"valueMetadata" => block = 2,
+                        b"rvb" if block == 1 => future_rvs.push(get_attr_val!(e, "i", parse)),
+                        b"rc" if block == 2 => value_rcs.push(get_attr_val!(e, "v", parse)),
+                        _ => ()
+                    }
+                },
+                Ok(Event::End(ref e)) if matches!(e.local_name().as_ref(), b"futureMetadata" | b"valueMetadata") => block = 0,
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(XlsxError::from_xml("xl/metadata.xml", e)),
+                _ => ()
+            }
+            buf.clear();
+        }
+    }
+    //  每个结构中LocalImageIdentifier的位置
+    let mut key_pos: Vec<Option<usize>> = Vec::new();
+    if let Ok(file) = open_part(zip_archive, "xl/richData/rdrichvaluestructure.xml", limits) {
+        let mut reader = Reader::from_reader(BufReader::new(file));
+        let mut buf = Vec::new();
+        let mut nkey = 0;
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                    match e.local_name().as_ref() {
+                        b"s" => {
+                            key_pos.push(None);
+                            nkey = 0;
+                        },
+                        b"k" => {
+                            if get_attr_val!(e, "n").as_ref() == "_rvRel:LocalImageIdentifier" {
+                                if let Some(pos) = key_pos.last_mut() {
+                                    *pos = Some(nkey);
+                                }
+                            }
+                            nkey += 1;
+                        },
+                        _ => ()
+                    }
+                },
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(XlsxError::from_xml("xl/richData/rdrichvaluestructure.xml", e)),
+                _ => ()
+            }
+            buf.clear();
+        }
+    }
+    //  rich value -> richValueRel的序号
+    let mut rv_rels: Vec<Option<usize>> = Vec::new();
+    if let Ok(file) = open_part(zip_archive, "xl/richData/rdrichvalue.xml", limits) {
+        let mut reader = Reader::from_reader(BufReader::new(file));
+        let mut buf = Vec::new();
+        let (mut pos, mut nval, mut in_v) = (None, 0, false);
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) => {
+                    match e.local_name().as_ref() {
+                        b"rv" => {
+                            let s: usize = get_attr_val!(e, "s", parse);
+                            pos = key_pos.get(s).copied().flatten();
+                            nval = 0;
+                            rv_rels.push(None);
+                        },
+                        b"v" => in_v = true,
+                        _ => ()
+                    }
+                },
+                Ok(Event::Text(ref t)) if in_v && pos == Some(nval) => {
+                    if let Some(rel) = rv_rels.last_mut() {
+                        *rel = t.unescape()?.trim().parse().ok();
+                    }
+                },
+                Ok(Event::End(ref e)) if e.local_name().as_ref() == b"v" => {
+                    in_v = false;
+                    nval += 1;
+                },
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(XlsxError::from_xml("xl/richData/rdrichvalue.xml", e)),
+                _ => ()
+            }
+            buf.clear();
+        }
+    }
+    //  richValueRel序号 -> 图片
+    let rel_part = "xl/richData/richValueRel.xml";
+    let rels = read_part_rels(zip_archive, rel_part, limits)?;
+    let mut rel_images = Vec::new();
+    if let Ok(file) = open_part(zip_archive, rel_part, limits) {
+        let mut reader = NsReader::from_reader(BufReader::new(file));
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"rel" => {
+                    let rid = get_rel_attr(&reader, e, "id")?.unwrap_or_default();
+                    rel_images.push(rels.get(&rid).filter(|r| !r.external).map(|r| resolve_part(rel_part, &r.target)));
+                },
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(XlsxError::from_xml(rel_part, e)),
+                _ => ()
+            }
+            buf.clear();
+        }
+    }
+    for (i, rc) in value_rcs.iter().enumerate() {
+        let rv = future_rvs.get(*rc);
+        let image = rv.and_then(|rv| rv_rels.get(*rv).copied().flatten()).and_then(|rel| rel_images.get(rel).cloned().flatten());
+        if let Some(part) = image {
+            images.insert(i as u32 + 1, part);
+        }
+    }
+    Ok(images)
+}
+
+impl XlsxBook {
+    /// get images of the sheet, including pictures in drawings and in-cell pictures (richData) of Excel 365.   
+    /// the raw bytes can be read by open_image
+    pub fn get_images(&mut self, sht_name: &str) -> Result<Vec<SheetImage>> {
+        let Some(part) = self.map_sheet.get(sht_name).cloned() else {
+            return Err(XlsxError::SheetNotFound(sht_name.to_string()));
+        };
+        let limits = &self.options.limits;
+        let mut images = Vec::new();
+        let sheet_rels = read_part_rels(&mut self.zip_archive, &part, limits)?;
+        for rel in sheet_rels.values().filter(|r| r.is("drawing") && !r.external) {
+            images.extend(read_drawing(&mut self.zip_archive, &resolve_part(&part, &rel.target), limits)?.0);
+        }
+        //  按锚定位置排序，保证结果稳定
+        images.sort_by_key(|v| v.range.map(|(from, _)| from));
+        let rich_images = read_rich_value_images(&mut self.zip_archive, limits)?;
+        if !rich_images.is_empty() {
+            let file = open_part(&mut self.zip_archive, &part, limits)?;
+            let mut reader = Reader::from_reader(BufReader::new(file));
+            let mut buf = Vec::with_capacity(8*1024);
+            loop {
+                match reader.read_event_into(&mut buf) {
+                    Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"c" => {
+                        if let Some(vm) = e.try_get_attribute("vm")? {
+                            let vm: u32 = vm.unescape_value()?.parse()?;
+                            if let Some(image) = rich_images.get(&vm) {
+                                let cell = get_tuple_from_ord(get_attr_val!(e, "r").as_bytes())?;
+                                images.push(SheetImage { part: image.clone(), content_type: None, name: None, descr: None, anchor: AnchorKind::InCell, range: Some((cell, cell)), size: None });
+                            }
+                        }
+                    },
+                    Ok(Event::Eof) => break,
+                    Err(e) => return Err(XlsxError::from_xml("sheet data", e)),
+                    _ => ()
+                }
+                buf.clear();
+            }
+        }
+        let types = ContentTypes::read(&mut self.zip_archive, limits)?;
+        for image in images.iter_mut() {
+            image.content_type = types.get(&image.part);
+        }
+        Ok(images)
+    }
+    /// get charts of the sheet (chart types, titles, series with references and cached values), sorted by anchor position.   
+    /// cached points out of ptCount are skipped and recorded as warnings
+    pub fn get_charts(&mut self, sht_name: &str) -> Result<Vec<SheetChart>> {
+        let Some(part) = self.map_sheet.get(sht_name).cloned() else {
+            return Err(XlsxError::SheetNotFound(sht_name.to_string()));
+        };
+        let limits = &self.options.limits;
+        let mut charts = Vec::new();
+        let sheet_rels = read_part_rels(&mut self.zip_archive, &part, limits)?;
+        for rel in sheet_rels.values().filter(|r| r.is("drawing") && !r.external) {
+            charts.extend(read_drawing(&mut self.zip_archive, &resolve_part(&part, &rel.target), limits)?.1);
+        }
+        for chart in charts.iter_mut() {
+            read_chart(&mut self.zip_archive, chart, limits, &mut self.warnings)?;
+        }
+        charts.sort_by_key(|v| v.range.map(|(from, _)| from));
+        Ok(charts)
+    }
+    /// get charts of all sheets, keyed by sheet name. sheets without charts are excluded
+    pub fn get_all_charts(&mut self) -> Result<HashMap<String, Vec<SheetChart>>> {
+        let names: Vec<String> = self.map_sheet.keys().cloned().collect();
+        let mut all = HashMap::new();
+        for name in names {
+            let charts = self.get_charts(&name)?;
+            if !charts.is_empty() {
+                all.insert(name, charts);
+            }
+        }
+        Ok(all)
+    }
+    /// open the raw bytes of the image for streaming read
+    pub fn open_image(&mut self, image: &SheetImage) -> Result<impl Read + '_> {
+        open_part(&mut self.zip_archive, &image.part, &self.options.limits)
+    }
+}
@@ -0,0 +1,188 @@
+use std::{collections::HashMap, fs::File, io::BufReader};
+use zip::ZipArchive;
+use quick_xml::{events::Event, reader::NsReader};
+use crate::{error::{Result, XlsxError}, get_tuple_from_ord, CellValue, ColNum, RowNum};
+use super::{get_rel_attr, open_part, read_part_rels, ReadOptions, XlsxBook};
+
+/// cached data of a sheet in the external workbook
+#[derive(Debug, Clone, Default)]
+pub struct ExternalSheetData {
+    pub name: String,
+    /// cached values by (row, col)
+    pub cells: HashMap<(RowNum, ColNum), CellValue<'static>>,
+}
+
+/// link to an external workbook, whose cached values are stored in `xl/externalLinks/externalLinkN.xml`
+#[derive(Debug, Clone, Default)]
+pub struct ExternalLink {
+    /// index referred by formulas (start from 1), e.g. `[1]Sheet1!A1`
+    pub index: usize,
+    /// part, e.g. `xl/externalLinks/externalLink1.xml`
+    pub part: String,
+    /// path or url of the external workbook
+    pub target: Option<String>,
+    pub sheet_names: Vec<String>,
+    /// defined names: (name, refers to)
+    pub defined_names: Vec<(String, String)>,
+    /// cached sheet data, sheets without cached data are excluded
+    pub sheets: Vec<ExternalSheetData>,
+}
+
+impl ExternalLink {
+    /// file name of the target, e.g. `Book2.xlsx` of `file:///C:\data\Book2.xlsx`
+    pub fn file_name(&self) -> Option<&str> {
+        self.target.as_deref().and_then(|v| v.rsplit(['/', '\\']).next()).filter(|v| !v.is_empty())
+    }
+    /// get cached value by sheet name and address, None if not cached
+    pub fn get_cell_value(&self, sheet: &str, addr: &str) -> Result<Option<&CellValue<'static>>> {
+        let cell = get_tuple_from_ord(addr.trim().replace('$', "").as_bytes())?;
+        Ok(self.sheets.iter().find(|v| v.name == sheet).and_then(|v| v.cells.get(&cell)))
+    }
+}
+
+/// parse external link part with cached sheet data
+fn read_external_link(zip_archive: &mut ZipArchive<BufReader<File>>, part: &str, options: &ReadOptions, warnings: &mut Vec<String>) -> Result<ExternalLink> {
+    let rels = read_part_rels(zip_archive, part, &options.limits)?;
+    let file = open_part(zip_archive, part, &options.limits)?;
+    let mut reader = NsReader::from_reader(BufReader::new(file));
+    let mut buf = Vec::new();
+    let mut link = ExternalLink { part: part.to_string(), ..Default::default() };
+    let mut cell: Option<((RowNum, ColNum), String)> = None;    //  地址、类型
+    let mut in_v = false;
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                match e.local_name().as_ref() {
+                    b"externalBook" => {
+                        if let Some(rid) = get_rel_attr(&reader, e, "id")? {
+                            link.target = rels.get(&rid).map(|r| r.target.clone());
+                        }
+                    },
+                    b"sheetName" => link.sheet_names.push(get_attr_val!(e, "val", to_string)),
+                    b"definedName" => {
+                        let refers_to = match e.try_get_attribute("refersTo")? {
+                            Some(v) => v.unescape_value()?.to_string(),
+                            None => String::new()
+                        };
+                        link.defined_names.push((get_attr_val!(e, "name", to_string), refers_to));
+                    },
+                    b"sheetData" => {
+                        let id: usize = get_attr_val!(e, "sheetId", parse);
+                        link.sheets.push(ExternalSheetData { name: link.sheet_names.get(id).cloned().unwrap_or_default(), cells: HashMap::new() });
+                    },
+                    b"cell" => {
+                        let addr = get_tuple_from_ord(get_attr_val!(e, "r").as_bytes())?;
+                        let t = match e.try_get_attribute("t")? {
+                            Some(v) => v.unescape_value()?.to_string(),
+                            None => "n".to_string()
+                        };
+                        cell = Some((addr, t));
+                    },
+                    b"v" => in_v = true,
+                    _ => ()
+                }
+            },
+            Ok(Event::Text(ref t)) if in_v => {
+                if let (Some((addr, t_type)), Some(sheet)) = (cell.as_ref(), link.sheets.last_mut()) {
+                    let text = t.unescape()?.to_string();
+                    //  外部链接中的字符串直接保存在v中
+                    let val = match t_type.as_str() {
+                        "s" | "str" | "inlineStr" => CellValue::String(text),
+                        "b" => CellValue::Bool(text.trim() == "1"),
+                        "e" => CellValue::Error(text),
+                        _ => match text.trim().parse() {
+                            Ok(v) => CellValue::Number(v),
+                            Err(_) => {
+                                let err = XlsxError::Conversion { sheet: Some(part.to_string()), row: Some(addr.0), col: Some(addr.1), value: text.clone(), target: "f64" };
+                                if options.strict {
+                                    return Err(err);
+                                }
+                                //  宽松模式下，无法解析的数值保留为字符串
+                                warnings.push(err.to_string());
+                                CellValue::String(text)
+                            }
+                        }
+                    };
+                    sheet.cells.insert(*addr, val);
+                }
+            },
+            Ok(Event::End(ref e)) => {
+                match e.local_name().as_ref() {
+                    b"v" => in_v = false,
+                    b"cell" => cell = None,
+                    _ => ()
+                }
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(XlsxError::from_xml(part, e)),
+            _ => ()
+        }
+        buf.clear();
+    }
+    Ok(link)
+}
+
+/// resolve external references in the formula, e.g. `[1]Sheet1!A1` -> `[Book2.xlsx]Sheet1!A1`.
+/// the sheet is quoted if the file name contains special characters, e.g. `'[My Book.xlsx]Sheet1'!A1`
+pub fn resolve_external_refs(formula: &str, links: &[ExternalLink]) -> String {
+    let chars: Vec<char> = formula.chars().collect();
+    let mut out = String::with_capacity(formula.len());
+    let (mut i, mut in_str) = (0, false);
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '"' {
+            in_str = !in_str;    //  字符串常量中的内容不处理
+        }
+        if in_str || c != '[' {
+            out.push(c);
+            i += 1;
+            continue;
+        }
+        let digits: String = chars[i+1..].iter().take_while(|v| v.is_ascii_digit()).collect();
+        let end = i + 1 + digits.len();
+        let link = digits.parse::<usize>().ok().and_then(|n| links.iter().find(|v| v.index == n));
+        match (link.and_then(|v| v.file_name()), chars.get(end)) {
+            (Some(name), Some(']')) => {
+                let quoted = out.ends_with('\'');
+                let special = name.chars().any(|v| !(v.is_alphanumeric() || v == '_' || v == '.'));
+                if !quoted && special {
+                    //  引号包括工作表名称，直到!
+                    let sheet_end = chars[end+1..].iter().position(|v| *v == '!').map(|p| end + 1 + p).unwrap_or(end + 1);
+                    let sheet: String = chars[end+1..sheet_end].iter().collect();
+                    out.push_str(&format!("'[{}]{}'", name.replace('\'', "''"), sheet));
+                    i = sheet_end;
+                } else {
+                    out.push_str(&format!("[{}]", if quoted {name.replace('\'', "''")} else {name.to_string()}));
+                    i = end + 1;
+                }
+            },
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+impl XlsxBook {
+    /// get external workbook links with cached sheet data, in the order referred by formulas (`[1]`, `[2]`, ...)
+    pub fn get_external_links(&mut self) -> Result<Vec<ExternalLink>> {
+        let refs = self.read_workbook_refs(b"externalReference", None)?;
+        let mut links = Vec::with_capacity(refs.len());
+        for (i, (_, part)) in refs.into_iter().enumerate() {
+            //  序号按workbook.xml中的位置，缺少关系的链接也占用序号
+            let Some(part) = part else {
+                if self.options.strict {
+                    return Err(XlsxError::MalformedXml(format!("Relationship of externalReference-{} not found", i + 1)));
+                }
+                self.warnings.push(format!("externalReference-{}: relationship not found", i + 1));
+                continue;
+            };
+            let mut link = read_external_link(&mut self.zip_archive, &part, &self.options, &mut self.warnings)?;
+            link.index = i + 1;
+            links.push(link);
+        }
+        Ok(links)
+    }
+}
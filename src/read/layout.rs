@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+use crate::{get_tuple_from_ord, ColNum, RowNum};
+
+/// filter criteria of a column in auto filter
+#[derive(Debug, Clone, Default)]
+pub struct FilterColumn {
+    /// column offset from the first column of the auto filter range (start from 0)
+    pub col_id: ColNum,
+    /// values to show
+    pub values: Vec<String>,
+    /// show blank cells
+    pub blank: bool,
+    /// custom filters: (operator, value), e.g. `("greaterThan", "5")`
+    pub custom_filters: Vec<(String, String)>,
+    /// custom filters are combined by and
+    pub custom_and: bool,
+}
+
+/// auto filter of a sheet
+#[derive(Debug, Clone, Default)]
+pub struct AutoFilter {
+    /// range, e.g. `A1:D100`
+    pub range: String,
+    pub columns: Vec<FilterColumn>,
+}
+
+/// pane of the sheet view, frozen rows and columns if frozen
+#[derive(Debug, Clone, Default)]
+pub struct SheetPane {
+    /// columns of the left pane (frozen columns if frozen)
+    pub x_split: f64,
+    /// rows of the top pane (frozen rows if frozen)
+    pub y_split: f64,
+    /// top left cell of the bottom right pane
+    pub top_left_cell: Option<String>,
+    pub frozen: bool,
+}
+
+/// width and visibility of columns min..=max
+#[derive(Debug, Clone, Default)]
+pub struct ColumnInfo {
+    pub min: ColNum,
+    pub max: ColNum,
+    pub width: Option<f64>,
+    pub hidden: bool,
+    pub custom_width: bool,
+}
+
+/// height and visibility of a row
+#[derive(Debug, Clone, Default)]
+pub struct RowInfo {
+    pub height: Option<f64>,
+    pub hidden: bool,
+    pub custom_height: bool,
+}
+
+/// layout of a sheet: auto filter, pane, default sizes, column widths and row heights
+#[derive(Debug, Clone, Default)]
+pub struct SheetLayout {
+    pub auto_filter: Option<AutoFilter>,
+    /// pane of the first sheet view
+    pub pane: Option<SheetPane>,
+    pub default_row_height: Option<f64>,
+    pub default_col_width: Option<f64>,
+    pub base_col_width: Option<f64>,
+    pub columns: Vec<ColumnInfo>,
+    /// rows with height or hidden set, collected by XlsxSheet::get_layout only
+    pub rows: BTreeMap<RowNum, RowInfo>,
+}
+
+impl SheetLayout {
+    /// guess the header row by frozen rows or auto filter, which is usually the last frozen row or the first row of the auto filter range
+    pub fn header_hint(&self) -> Option<RowNum> {
+        if let Some(pane) = &self.pane {
+            if pane.frozen && pane.y_split >= 1.0 {
+                return Some(pane.y_split as RowNum);
+            }
+        }
+        let range = &self.auto_filter.as_ref()?.range;
+        let first = range.split(':').next()?;
+        get_tuple_from_ord(first.as_bytes()).ok().map(|(r, _)| r)
+    }
+    /// get width of the column, None if the width is not set and no default width
+    pub fn column_width(&self, col: ColNum) -> Option<f64> {
+        self.columns.iter().find(|c| col >= c.min && col <= c.max).and_then(|c| c.width).or(self.default_col_width)
+    }
+}
@@ -1,14 +1,15 @@
-use std::{cmp::max, collections::{HashMap, HashSet}, fs::File, io::BufReader, path::Path};
-use anyhow::{anyhow, Result};
-use zip::{ZipArchive, read::ZipFile};
+use std::{cmp::max, collections::{HashMap, HashSet, VecDeque}, fs::{File, OpenOptions}, io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write}, path::{Path, PathBuf}, sync::{atomic::{AtomicBool, AtomicUsize, Ordering}, Arc, Mutex}};
+use zip::{ZipArchive, read::ZipFile, result::ZipError};
 use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
-use quick_xml::{events::Event, reader::Reader};
+use quick_xml::{events::{BytesStart, Event}, name::ResolveResult, reader::{NsReader, Reader}};
 
 use lazy_static::lazy_static;
-use crate::{get_num_from_ord, get_tuple_from_ord, CellValue, ColNum, Date32, MergedRange, RowNum, Timesecond, Timestamp, MAX_COL_NUM};
+use crate::{error::{LimitError, Result, XlsxError}, get_num_from_ord, get_ord_from_tuple, get_tuple_from_ord, CellValue, ColNum, Date32, MergedRange, MergedRanges, RowNum, Timesecond, Timestamp, MAX_COL_NUM};
 
-#[cfg(feature = "cached")]
-use crate::is_merged_cell;
+#[cfg(feature = "vba")]
+mod vba;
+#[cfg(feature = "vba")]
+pub use vba::{VbaModule, VbaModuleKind, VbaProject};
 
 // ooxml： http://www.officeopenxml.com/
 
@@ -16,23 +17,41 @@ macro_rules! get_attr_val {
     ($e:expr, $tag:expr) => {
         match $e.try_get_attribute($tag)? {
             Some(v) => {v.unescape_value()?},
-            None => return Err(anyhow!("attribute {} not exist", $tag))
+            None => return Err(XlsxError::MissingAttribute($tag.to_string()))
         }
     };
     ($e:expr, $tag:expr, parse) => {
         match $e.try_get_attribute($tag)? {
             Some(v) => {v.unescape_value()?.parse()?},
-            None => return Err(anyhow!("attribute {} not exist", $tag))
+            None => return Err(XlsxError::MissingAttribute($tag.to_string()))
         }
     };
     ($e:expr, $tag:expr, to_string) => {
         match $e.try_get_attribute($tag)? {
             Some(v) => {v.unescape_value()?.to_string()},
-            None => return Err(anyhow!("attribute {} not exist", $tag))
+            None => return Err(XlsxError::MissingAttribute($tag.to_string()))
         }
     };
 }
 
+mod cond_format;
+mod drawing;
+mod external;
+mod layout;
+mod pivot;
+mod properties;
+mod protection;
+mod validation;
+pub use cond_format::{CfRule, CfRuleKind, ConditionalFormat, DxfStyle};
+pub use drawing::{AnchorKind, ChartSeries, SheetChart, SheetImage};
+pub use external::{resolve_external_refs, ExternalLink, ExternalSheetData};
+pub use layout::{AutoFilter, ColumnInfo, FilterColumn, RowInfo, SheetLayout, SheetPane};
+pub use pivot::{PivotCache, PivotCacheField, PivotDataField, PivotRecords, PivotTable};
+pub use properties::{BookProperties, PropertyValue};
+pub use protection::{CellProtection, PasswordHash, ProtectedRange, SheetProtection, UnlockedCells, WorkbookProtection};
+pub use validation::{DataValidation, ValidationKind, ValidationOperator, ValidationViolation};
+use cond_format::get_color_attr;
+
 /// check if row is matched
 fn is_matched_row(row: &Vec<CellValue<'_>>, checks: &HashMap<usize, HashSet<String>>, check_by_and: bool) -> (bool, String) {
     if check_by_and {
@@ -64,48 +83,922 @@ fn is_matched_row(row: &Vec<CellValue<'_>>, checks: &HashMap<usize, HashSet<Stri
     }
 }
 
+/// flatten header rows into column names like `Q1.Revenue`.
+/// the group captions of upper rows are propagated across merged ranges
+fn flatten_header_rows(rows: &[(u32, Vec<CellValue<'_>>)], left_ncol: ColNum, merged_rects: Option<&MergedRanges>) -> Vec<String> {
+    let ncol = rows.iter().map(|(_, r)| r.len()).max().unwrap_or(0);
+    let mut grid: Vec<Vec<String>> = rows.iter().map(|(_, r)| {
+        (0..ncol).map(|j| {
+            match r.get(j).map(|c| c.get::<String>()) {
+                Some(Ok(Some(s))) => s.trim().to_string(),
+                _ => String::new()
+            }
+        }).collect()
+    }).collect();
+    for i in 0..grid.len() {
+        for j in 0..ncol {
+            if !grid[i][j].is_empty() {
+                continue;
+            }
+            if let Some(((left_top, _), _, _)) = merged_rects.and_then(|mgs| mgs.lookup(rows[i].0, left_ncol + j as ColNum + 1)) {
+                //  使用合并区域左上角单元格的值填充
+                if let Some(ti) = rows.iter().position(|(r, _)| *r == left_top.0) {
+                    if left_top.1 > left_ncol && ((left_top.1 - left_ncol - 1) as usize) < ncol {
+                        let caption = grid[ti][(left_top.1 - left_ncol - 1) as usize].clone();
+                        grid[i][j] = caption;
+                    }
+                }
+            }
+        }
+    }
+    (0..ncol).map(|j| {
+        let mut parts: Vec<&str> = Vec::with_capacity(grid.len());
+        for row in &grid {
+            let s = row[j].as_str();
+            if !s.is_empty() && parts.last() != Some(&s) {
+                parts.push(s);
+            }
+        }
+        parts.join(".")
+    }).collect()
+}
+
+/// parse merged range from address like `A1:B2`
+fn get_merged_range(attr: &str) -> Result<MergedRange> {
+    match attr.split_once(':') {
+        Some((left_top, right_end)) => Ok((get_tuple_from_ord(left_top.as_bytes())?, get_tuple_from_ord(right_end.as_bytes())?)),
+        None => Err(XlsxError::MalformedXml(format!("mergeCell error：{}", attr)))
+    }
+}
+
+/// scan trailing elements of the sheet (after sheetData) with a new reader, at the cost of one extra decompression pass.   
+/// the layout of rows is collected only if with_rows is true
+fn scan_sheet_tail(path: &Path, part: &str, options: &ReadOptions, with_rows: bool, warnings: &mut Vec<String>) -> Result<SheetTail> {
+    let mut zip_archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+    let sheet_refs = read_part_rels(&mut zip_archive, part, &options.limits)?;
+    let file = open_part(&mut zip_archive, part, &options.limits)?;
+    let mut reader = NsReader::from_reader(BufReader::new(file));
+    let mut buf = Vec::with_capacity(8*1024);
+    let mut tail = SheetTail::default();
+    let mut merged_rects = Vec::new();
+    let mut validation: Option<DataValidation> = None;
+    let mut formula_no = 0;    //  1-formula1; 2-formula2; 3-sqref; 4-条件格式formula; 5-条件格式sqref
+    let mut cond_format: Option<ConditionalFormat> = None;
+    let mut cf_rule: Option<CfRule> = None;
+    let mut filter_col: Option<FilterColumn> = None;
+    let mut in_sheet_view = false;    //  只读取第一个sheetView的pane
+    let attr_f64 = |e: &quick_xml::events::BytesStart<'_>, tag: &str| -> Result<Option<f64>> {
+        match e.try_get_attribute(tag)? {
+            Some(v) => Ok(v.unescape_value()?.parse().ok()),
+            None => Ok(None)
+        }
+    };
+    let attr_flag = |e: &quick_xml::events::BytesStart<'_>, tag: &str| -> Result<bool> {
+        match e.try_get_attribute(tag)? {
+            Some(v) => Ok(matches!(v.unescape_value()?.as_ref(), "1" | "true")),
+            None => Ok(false)
+        }
+    };
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if filter_col.is_some() => {
+                if let Some(fc) = filter_col.as_mut() {
+                    match e.local_name().as_ref() {
+                        b"filters" => fc.blank = attr_flag(e, "blank")?,
+                        b"filter" => fc.values.push(get_attr_val!(e, "val", to_string)),
+                        b"customFilters" => fc.custom_and = attr_flag(e, "and")?,
+                        b"customFilter" => {
+                            let op = match e.try_get_attribute("operator")? {
+                                Some(v) => v.unescape_value()?.to_string(),
+                                None => "equal".to_string()
+                            };
+                            fc.custom_filters.push((op, get_attr_val!(e, "val", to_string)));
+                        },
+                        _ => ()
+                    }
+                }
+            },
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"filterColumn" => {
+                if let (Some(af), Some(fc)) = (tail.layout.auto_filter.as_mut(), filter_col.take()) {
+                    af.columns.push(fc);
+                }
+            },
+            Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"filterColumn" => {
+                filter_col = Some(FilterColumn { col_id: get_attr_val!(e, "colId", parse), ..Default::default() });
+            },
+            Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"filterColumn" => {
+                if let Some(af) = tail.layout.auto_filter.as_mut() {
+                    af.columns.push(FilterColumn { col_id: get_attr_val!(e, "colId", parse), ..Default::default() });
+                }
+            },
+            Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"conditionalFormatting" => {
+                let sqref = match e.try_get_attribute("sqref")? {
+                    Some(v) => v.unescape_value()?.to_string(),
+                    None => String::new()    //  x14扩展中sqref为子元素
+                };
+                cond_format = Some(ConditionalFormat { sqref, ranges: Vec::new(), rules: Vec::new() });
+            },
+            Ok(Event::Empty(ref e)) if cond_format.is_some() && e.local_name().as_ref() == b"cfRule" => {
+                let rule = CfRule::from_attrs(e)?;
+                if let Some(cf) = cond_format.as_mut() {
+                    cf.rules.push(rule);
+                }
+            },
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if cond_format.is_some() => {
+                match e.local_name().as_ref() {
+                    b"cfRule" => cf_rule = Some(CfRule::from_attrs(e)?),
+                    b"formula" | b"f" => {
+                        if let Some(rule) = cf_rule.as_mut() {
+                            rule.formulas.push(String::new());
+                            formula_no = 4;
+                        }
+                    },
+                    b"sqref" => formula_no = 5,
+                    b"color" => {
+                        if let Some(rule) = cf_rule.as_mut() {
+                            if let Some(color) = get_color_attr(e)? {
+                                rule.colors.push(color);
+                            }
+                        }
+                    },
+                    _ => ()
+                }
+            },
+            Ok(Event::End(ref e)) if cond_format.is_some() => {
+                match e.local_name().as_ref() {
+                    b"formula" | b"f" | b"sqref" => formula_no = 0,
+                    b"cfRule" => {
+                        if let (Some(cf), Some(rule)) = (cond_format.as_mut(), cf_rule.take()) {
+                            cf.rules.push(rule);
+                        }
+                    },
+                    b"conditionalFormatting" => {
+                        if let Some(mut cf) = cond_format.take() {
+                            match parse_sqref(&cf.sqref) {
+                                Ok(ranges) => {
+                                    cf.ranges = ranges;
+                                    tail.conditional_formats.push(cf);
+                                },
+                                Err(e) if options.strict => return Err(e),
+                                Err(e) => warnings.push(e.to_string())
+                            };
+                        }
+                    },
+                    _ => ()
+                }
+            },
+            Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"dataValidation" => {
+                let mut dv = DataValidation::from_attrs(e)?;
+                match dv.finish() {
+                    Ok(()) => tail.data_validations.push(dv),
+                    Err(e) if options.strict => return Err(e),
+                    Err(e) => warnings.push(e.to_string())
+                };
+            },
+            Ok(Event::Start(ref e)) if validation.is_some() => {
+                formula_no = match e.local_name().as_ref() {
+                    b"formula1" => 1,
+                    b"formula2" => 2,
+                    b"sqref" => 3,
+                    _ => formula_no
+                };
+            },
+            Ok(Event::Text(ref t)) if formula_no > 0 => {
+                let text = t.unescape()?;
+                if let Some(dv) = validation.as_mut() {
+                    match formula_no {
+                        1 => dv.formula1.get_or_insert_with(String::new).push_str(&text),
+                        2 => dv.formula2.get_or_insert_with(String::new).push_str(&text),
+                        _ => dv.sqref.push_str(&text)
+                    };
+                } else if let Some(cf) = cond_format.as_mut() {
+                    match (formula_no, cf_rule.as_mut().and_then(|r| r.formulas.last_mut())) {
+                        (4, Some(formula)) => formula.push_str(&text),
+                        (5, _) => cf.sqref.push_str(&text),
+                        _ => ()
+                    };
+                }
+            },
+            Ok(Event::End(ref e)) if validation.is_some() => {
+                match e.local_name().as_ref() {
+                    b"formula1" | b"formula2" | b"sqref" => formula_no = 0,
+                    b"dataValidation" => {
+                        if let Some(mut dv) = validation.take() {
+                            match dv.finish() {
+                                Ok(()) => tail.data_validations.push(dv),
+                                Err(e) if options.strict => return Err(e),
+                                Err(e) => warnings.push(e.to_string())
+                            };
+                        }
+                    },
+                    _ => ()
+                }
+            },
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                match e.local_name().as_ref() {
+                    b"mergeCell" => {
+                        match get_merged_range(&get_attr_val!(e, "ref", to_string)) {
+                            Ok(rng) => merged_rects.push(rng),
+                            Err(e) if options.strict => return Err(e),
+                            Err(e) => warnings.push(e.to_string())
+                        };
+                    },
+                    b"hyperlink" => {
+                        let rid = get_rel_attr(&reader, e, "id")?;
+                        let attr_val = |tag: &str| -> Result<Option<String>> {
+                            match e.try_get_attribute(tag)? {
+                                Some(v) => Ok(Some(v.unescape_value()?.to_string())),
+                                None => Ok(None)
+                            }
+                        };
+                        tail.hyperlinks.push(Hyperlink {
+                            range: get_attr_val!(e, "ref", to_string),
+                            target: rid.as_ref().and_then(|v| sheet_refs.get(v).map(|r| r.target.clone())),
+                            location: attr_val("location")?,
+                            display: attr_val("display")?,
+                        });
+                    },
+                    b"dataValidation" => {
+                        validation = Some(DataValidation::from_attrs(e)?);
+                    },
+                    b"autoFilter" => {
+                        let range = get_attr_val!(e, "ref", to_string);
+                        tail.auto_filter = Some(range.clone());
+                        tail.layout.auto_filter = Some(AutoFilter { range, columns: Vec::new() });
+                    },
+                    b"sheetView" => in_sheet_view = tail.layout.pane.is_none(),
+                    b"pane" if in_sheet_view => {
+                        let frozen = match e.try_get_attribute("state")? {
+                            Some(v) => matches!(v.unescape_value()?.as_ref(), "frozen" | "frozenSplit"),
+                            None => false
+                        };
+                        let top_left_cell = match e.try_get_attribute("topLeftCell")? {
+                            Some(v) => Some(v.unescape_value()?.to_string()),
+                            None => None
+                        };
+                        tail.layout.pane = Some(SheetPane { x_split: attr_f64(e, "xSplit")?.unwrap_or(0.0), y_split: attr_f64(e, "ySplit")?.unwrap_or(0.0), top_left_cell, frozen });
+                        in_sheet_view = false;
+                    },
+                    b"sheetProtection" => tail.protection = Some(SheetProtection::from_attrs(e)?),
+                    b"protectedRange" => {
+                        let sqref = get_attr_val!(e, "sqref", to_string);
+                        let ranges = match parse_sqref(&sqref) {
+                            Ok(v) => v,
+                            Err(e) if options.strict => return Err(e),
+                            Err(e) => {
+                                warnings.push(e.to_string());
+                                Vec::new()
+                            }
+                        };
+                        let security_descriptor = match e.try_get_attribute("securityDescriptor")? {
+                            Some(v) => Some(v.unescape_value()?.to_string()),
+                            None => None
+                        };
+                        tail.protected_ranges.push(ProtectedRange { name: get_attr_val!(e, "name", to_string), sqref, ranges, password: PasswordHash::from_attrs(e, "")?, security_descriptor });
+                    },
+                    b"sheetFormatPr" => {
+                        tail.layout.default_row_height = attr_f64(e, "defaultRowHeight")?;
+                        tail.layout.default_col_width = attr_f64(e, "defaultColWidth")?;
+                        tail.layout.base_col_width = attr_f64(e, "baseColWidth")?;
+                    },
+                    b"col" => {
+                        tail.layout.columns.push(ColumnInfo {
+                            min: get_attr_val!(e, "min", parse),
+                            max: get_attr_val!(e, "max", parse),
+                            width: attr_f64(e, "width")?,
+                            hidden: attr_flag(e, "hidden")?,
+                            custom_width: attr_flag(e, "customWidth")?,
+                        });
+                    },
+                    b"row" if with_rows => {
+                        let (height, hidden) = (attr_f64(e, "ht")?, attr_flag(e, "hidden")?);
+                        if height.is_some() || hidden {
+                            if let Some(r) = attr_f64(e, "r")? {
+                                tail.layout.rows.insert(r as RowNum, RowInfo { height, hidden, custom_height: attr_flag(e, "customHeight")? });
+                            }
+                        }
+                    },
+                    _ => ()
+                }
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(XlsxError::from_xml("sheet data", e)),
+            _ => ()
+        }
+        buf.clear();
+    }
+    tail.merged_ranges = MergedRanges::new(merged_rects);
+    Ok(tail)
+}
+
+/// parse number from the text without allocating
+fn parse_bytes<T: std::str::FromStr>(text: &[u8]) -> Option<T> {
+    std::str::from_utf8(text).ok()?.parse().ok()
+}
+
+/// parse cell value from the text of tag v or t
+fn parse_cell_value<'a>(text: &[u8], cell_type: &[u8], prev_head: &[u8], num_fmt_id: u32, str_share: &'a SharedStrings, datetime_fmts: &HashMap<u32, u8>) -> Result<CellValue<'a>> {
+    let conv = |target: &'static str| XlsxError::Conversion { sheet: None, row: None, col: None, value: String::from_utf8_lossy(text).to_string(), target };
+    let cel_val = if cell_type == b"inlineStr" && prev_head == b"t" { 
+        CellValue::String(String::from_utf8(text.to_vec()).map_err(|_| conv("String"))?)
+    } else if prev_head == b"v" {
+        if cell_type == b"s" {
+            let inx = parse_bytes::<usize>(text).ok_or_else(|| conv("shared string index"))?;
+            str_share.get_value(inx)?.ok_or_else(|| conv("shared string index"))?
+        } else if cell_type == b"n" {
+            let fmt = datetime_fmts.get(&num_fmt_id).unwrap_or(&FMT_DEFAULT);
+            let num = parse_bytes::<f64>(text).ok_or_else(|| conv("f64"))?;
+            if *fmt == FMT_DATE {
+                CellValue::Date(num)
+            } else if *fmt == FMT_DATETIME {
+                CellValue::Datetime(num)
+            } else if *fmt == FMT_TIME {
+                CellValue::Time(num)
+            } else {
+                CellValue::Number(num)
+            }
+        } else if cell_type == b"b" {
+            if parse_bytes::<usize>(text) == Some(1) {
+                CellValue::Bool(true)
+            } else {
+                CellValue::Bool(false)
+            }
+        } else if cell_type == b"d" {
+            let val = String::from_utf8(text.to_vec()).map_err(|_| conv("String"))?;
+            match parse_iso8601_cell(&val) {
+                Some(v) => v,
+                None => CellValue::String(val)    //  无法识别的日期保留原文
+            }
+        } else if cell_type == b"e" {
+            CellValue::Error(String::from_utf8(text.to_vec()).map_err(|_| conv("String"))?)
+        } else if cell_type == b"str" {
+            CellValue::String(String::from_utf8(text.to_vec()).map_err(|_| conv("String"))?)
+        } else{
+            CellValue::Blank
+        }
+    } else {
+        CellValue::Error("Unknown cell type".into())
+    };
+    Ok(cel_val)
+}
+
+/// guess the header row index, returns the index and the confidence (0.0 ~ 1.0)
+fn guess_header_row(rows: &[(u32, Vec<CellValue<'_>>)]) -> (usize, f64) {
+    let is_text = |c: &CellValue<'_>| match c {
+        CellValue::Shared(s) => s.trim().parse::<f64>().is_err(),
+        CellValue::String(s) => s.trim().parse::<f64>().is_err(),
+        _ => false
+    };
+    let is_blank = |c: &CellValue<'_>| match c {
+        CellValue::Blank => true,
+        CellValue::Shared(s) => s.trim().is_empty(),
+        CellValue::String(s) => s.trim().is_empty(),
+        _ => false
+    };
+    let filled: Vec<usize> = rows.iter().map(|(_, r)| r.iter().filter(|c| !is_blank(c)).count()).collect();
+    let width = max(1, filled.iter().copied().max().unwrap_or(0)) as f64;
+    let (mut best, mut best_score) = (0, 0.0);
+    for (i, (_, row)) in rows.iter().enumerate() {
+        if filled[i] == 0 {
+            continue;
+        }
+        let texts: HashSet<String> = row.iter()
+            .filter(|c| !is_blank(c) && is_text(c))
+            .filter_map(|c| c.get::<String>().ok().flatten())
+            .map(|s| s.trim().to_string())
+            .collect();
+        let density = texts.len() as f64 / width;          //  不重复文本占比
+        let purity = texts.len() as f64 / filled[i] as f64;   //  文本在非空单元格中的占比
+        let follows = &rows[i+1..std::cmp::min(i+4, rows.len())];
+        let consistency = if follows.is_empty() {
+            0.0
+        } else {
+            follows.iter().enumerate().map(|(k, (_, r))| {
+                let fill = (filled[i+k+1] as f64 / filled[i] as f64).min(1.0);
+                let typed = if r.iter().any(|c| !is_blank(c) && !is_text(c)) {1.0} else {0.5};
+                fill * typed
+            }).sum::<f64>() / follows.len() as f64
+        };
+        let score = density * 0.4 + purity * 0.3 + consistency * 0.3;
+        if score > best_score {
+            (best, best_score) = (i, score);
+        }
+    }
+    (best, best_score.clamp(0.0, 1.0))
+}
+
+/// parse W3CDTF datetime, e.g. 2024-01-02T03:04:05Z
+fn parse_w3c_datetime(val: &str) -> Option<NaiveDateTime> {
+    match chrono::DateTime::parse_from_rfc3339(val) {
+        Ok(v) => Some(v.naive_utc()),
+        Err(_) => NaiveDateTime::parse_from_str(val.trim_end_matches('Z'), "%Y-%m-%dT%H:%M:%S%.f").ok()
+    }
+}
+
+/// parse ISO 8601 value of the date cell (`t="d"`), e.g. `2024-01-02`, `2024-01-02T03:04:05` or `T03:04:05`
+fn parse_iso8601_cell(val: &str) -> Option<CellValue<'static>> {
+    let val = val.trim();
+    let parse_time = |tm: &str| {
+        //  时区偏移不改变单元格的本地时间，直接去掉
+        let tm = tm.trim_end_matches('Z');
+        let tm = tm.rfind(['+', '-']).map_or(tm, |i| &tm[..i]);
+        NaiveTime::parse_from_str(tm, "%H:%M:%S%.f").or_else(|_| NaiveTime::parse_from_str(tm, "%H:%M")).ok()
+    };
+    match val.split_once('T') {
+        Some(("", tm)) => parse_time(tm)?.try_into_cval().ok(),
+        Some((dt, tm)) => match chrono::DateTime::parse_from_rfc3339(val) {
+            Ok(v) => v.naive_local().try_into_cval().ok(),
+            Err(_) => NaiveDate::parse_from_str(dt, "%Y-%m-%d").ok()?.and_time(parse_time(tm)?).try_into_cval().ok()
+        },
+        None if val.contains(':') => parse_time(val)?.try_into_cval().ok(),
+        None => NaiveDate::parse_from_str(val, "%Y-%m-%d").ok()?.try_into_cval().ok()
+    }
+}
+
+/// relationship of a part
+#[derive(Debug, Clone)]
+struct PartRel {
+    /// type, e.g. `http://schemas.openxmlformats.org/officeDocument/2006/relationships/image`
+    kind: String,
+    target: String,
+    external: bool,
+}
+
+impl PartRel {
+    /// check the type by the last segment, e.g. `image`, `drawing`
+    fn is(&self, kind: &str) -> bool {
+        self.kind.rsplit('/').next() == Some(kind)
+    }
+}
+
+/// path of the relationships part, e.g. `xl/worksheets/sheet1.xml` -> `xl/worksheets/_rels/sheet1.xml.rels`
+fn rels_part(part: &str) -> String {
+    match part.rsplit_once('/') {
+        Some((dir, name)) => format!("{dir}/_rels/{name}.rels"),
+        None => format!("_rels/{part}.rels")
+    }
+}
+
+/// read relationships of the part by id, empty if the relationships part does not exist
+fn read_part_rels(zip_archive: &mut ZipArchive<BufReader<File>>, part: &str, limits: &ReadLimits) -> Result<HashMap<String, PartRel>> {
+    let rels = rels_part(part);
+    let mut refs = HashMap::new();
+    let file = match open_part(zip_archive, &rels, limits) {
+        Ok(file) => file,
+        Err(XlsxError::MissingPart(_)) => return Ok(refs),
+        Err(e) => return Err(e)
+    };
+    let mut buf = Vec::new();
+    let mut reader = Reader::from_reader(BufReader::new(file));
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"Relationship" => {
+                let external = match e.try_get_attribute("TargetMode")? {
+                    Some(v) => v.unescape_value()?.as_ref() == "External",
+                    None => false
+                };
+                let kind = match e.try_get_attribute("Type")? {
+                    Some(v) => v.unescape_value()?.to_string(),
+                    None => String::new()
+                };
+                refs.insert(get_attr_val!(e, "Id", to_string), PartRel { kind, target: get_attr_val!(e, "Target", to_string), external });
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(XlsxError::from_xml(&rels, e)),
+            _ => ()
+        }
+        buf.clear();
+    }
+    Ok(refs)
+}
+
+/// resolve the target of a relationship into a part name, e.g. `../media/image1.png` of `xl/drawings/drawing1.xml` -> `xl/media/image1.png`
+fn resolve_part(source: &str, target: &str) -> String {
+    let mut segs: Vec<&str> = if let Some(abs) = target.strip_prefix('/') {
+        return abs.to_string();
+    } else {
+        source.rsplit_once('/').map(|(dir, _)| dir.split('/').collect()).unwrap_or_default()
+    };
+    for seg in target.split('/') {
+        match seg {
+            ".." => {segs.pop();},
+            "." | "" => (),
+            _ => segs.push(seg)
+        }
+    }
+    segs.join("/")
+}
+
+/// relationship namespaces of transitional and strict (ISO 29500 Strict) ooxml
+const REL_NAMESPACES: [&[u8]; 2] = [b"http://schemas.openxmlformats.org/officeDocument/2006/relationships", b"http://purl.oclc.org/ooxml/officeDocument/relationships"];
+
+/// get relationship attribute (e.g. `r:id`, `r:embed`) by local name, the prefix is resolved by namespace declarations.   
+/// the literal prefix `r` is accepted if it is not declared
+fn get_rel_attr<R>(reader: &NsReader<R>, e: &BytesStart<'_>, local: &str) -> Result<Option<String>> {
+    for attr in e.attributes() {
+        let attr = attr?;
+        let matched = match reader.resolve_attribute(attr.key) {
+            (ResolveResult::Bound(ns), name) => name.as_ref() == local.as_bytes() && REL_NAMESPACES.contains(&ns.as_ref()),
+            (ResolveResult::Unknown(prefix), name) => prefix == b"r" && name.as_ref() == local.as_bytes(),
+            _ => false
+        };
+        if matched {
+            return Ok(Some(attr.unescape_value()?.to_string()));
+        }
+    }
+    Ok(None)
+}
+
+/// hyperlink of a cell range
+#[derive(Debug, Clone)]
+pub struct Hyperlink {
+    /// cell range, such as `A1` or `A1:B2`
+    pub range: String,
+    /// external target, such as an url
+    pub target: Option<String>,
+    /// location in the workbook, such as `Sheet2!A1`
+    pub location: Option<String>,
+    /// display text
+    pub display: Option<String>,
+}
+
+/// parse sqref (space separated ranges or cells) into ranges
+fn parse_sqref(sqref: &str) -> Result<Vec<MergedRange>> {
+    sqref.split_whitespace().map(|v| {
+        if v.contains(':') {
+            get_merged_range(v)
+        } else {
+            let cell = get_tuple_from_ord(v.as_bytes())?;
+            Ok((cell, cell))
+        }
+    }).collect()
+}
+
+/// read boolean attribute, default value is used if the attribute is absent
+fn attr_bool(e: &quick_xml::events::BytesStart<'_>, tag: &str, default: bool) -> Result<bool> {
+    match e.try_get_attribute(tag)? {
+        Some(v) => Ok(matches!(v.unescape_value()?.as_ref(), "1" | "true")),
+        None => Ok(default)
+    }
+}
+
+/// trailing elements of a sheet, which are placed after sheet data in the xml file
+#[derive(Debug, Clone, Default)]
+pub struct SheetTail {
+    /// merged ranges
+    pub merged_ranges: MergedRanges,
+    /// hyperlinks
+    pub hyperlinks: Vec<Hyperlink>,
+    /// data validation rules
+    pub data_validations: Vec<DataValidation>,
+    /// conditional formatting
+    pub conditional_formats: Vec<ConditionalFormat>,
+    /// auto filter range
+    pub auto_filter: Option<String>,
+    /// sheet layout
+    pub layout: SheetLayout,
+    /// sheet protection, None if not set
+    pub protection: Option<SheetProtection>,
+    /// ranges allowed to edit when the sheet is protected
+    pub protected_ranges: Vec<ProtectedRange>,
+}
+
+/// 根据文件声明的数量预分配内存时的上限，避免恶意文件声明超大数量
+const MAX_RESERVED: usize = 1 << 20;
+
+/// resource limits to defend against zip bombs and huge inputs, None means no limit. an error XlsxError::LimitExceeded will be raised when exceeded
+#[derive(Debug, Clone, Default)]
+pub struct ReadLimits {
+    /// max uncompressed bytes of each part (file in the zip archive)
+    pub max_part_size: Option<u64>,
+    /// max ratio of uncompressed bytes to compressed bytes of each part, xml usually compresses 10~20 times
+    pub max_compression_ratio: Option<u64>,
+    /// max count of shared strings
+    pub max_shared_strings: Option<usize>,
+    /// max row number
+    pub max_rows: Option<RowNum>,
+    /// max column number
+    pub max_cols: Option<ColNum>,
+    /// max bytes of cell text (including shared strings)
+    pub max_cell_text: Option<usize>,
+}
+
+/// checkpoint of a sheet reader, to resume reading after a crash, see XlsxSheet::checkpoint and XlsxBook::resume_sheet
+#[derive(Debug, Clone)]
+pub struct SheetCheckpoint {
+    /// sheet name
+    pub sheet: String,
+    /// the last row returned (or skipped), rows before it (included) will not be returned again
+    pub row: RowNum,
+    /// column range, the same as XlsxSheet::column_range
+    pub column_range: (ColNum, ColNum),
+    /// header rows which have been read, empty if no header row or the header row has not been read
+    pub header_rows: Vec<(RowNum, Vec<CellValue<'static>>)>,
+    /// count of header rows to read, 0 means no header row
+    pub header_nrows: usize,
+    /// column names flattened from header rows
+    pub header_names: Option<Vec<String>>,
+    /// captured values
+    pub captured_vals: HashMap<String, CellValue<'static>>,
+    /// whether the row of with_skip_until has been matched (or with_skip_until not set)
+    pub skip_until_matched: bool,
+    /// whether the row of with_read_before has been matched, no more rows will be returned after resuming
+    pub read_before_matched: bool,
+}
+
+/// progress of reading a sheet, see XlsxSheet::with_progress
+#[derive(Debug, Clone, Default)]
+pub struct ReadProgress {
+    /// count of rows processed (including skipped rows)
+    pub rows: usize,
+    /// current row number
+    pub current_row: RowNum,
+    /// estimated total rows from the dimension of the sheet, None if no dimension
+    pub total_rows: Option<RowNum>,
+    /// uncompressed bytes consumed
+    pub bytes_read: u64,
+    /// uncompressed size of the sheet
+    pub total_bytes: u64,
+    /// compressed bytes consumed, estimated by the ratio of uncompressed bytes consumed
+    pub compressed_read: u64,
+    /// compressed size of the sheet (zip entry)
+    pub compressed_size: u64,
+}
+
+impl ReadProgress {
+    /// fraction of the sheet consumed (0.0 ~ 1.0)
+    pub fn fraction(&self) -> f64 {
+        if self.total_bytes == 0 {
+            1.0
+        } else {
+            (self.bytes_read as f64 / self.total_bytes as f64).min(1.0)
+        }
+    }
+}
+
+/// token to cancel reading from another thread, the reading stops between rows with XlsxError::Cancelled
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// create a new token
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// cancel reading
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+    /// whether cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// callback of progress
+type ProgressFn<'a> = Box<dyn FnMut(&ReadProgress) + Send + 'a>;
+
+/// options for reading xlsx book
+#[derive(Debug, Clone)]
+pub struct ReadOptions {
+    /// if false, tolerate the issues of corrupt or non-conforming files (e.g. invalid style index, wrong count of shared strings or merged ranges), record them as warnings and keep reading
+    pub strict: bool,
+    /// resource limits, the limits are enforced in lenient mode too
+    pub limits: ReadLimits,
+    /// spill shared strings into a temporary file if the count (uniqueCount) exceeds it, to save memory for huge workbooks. None means always keep them in memory.
+    /// Attention: spilled shared strings are read as CellValue::String instead of CellValue::Shared
+    pub shared_strings_spill: Option<usize>,
+    /// directory of the temporary file, std::env::temp_dir() is used if None
+    pub spill_dir: Option<PathBuf>,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        Self { strict: true, limits: ReadLimits::default(), shared_strings_spill: None, spill_dir: None }
+    }
+}
+
+/// shared strings, kept in memory or spilled into a temporary file
+enum SharedStrings {
+    Memory(Vec<String>),
+    Disk(DiskStrings),
+}
+
+impl SharedStrings {
+    fn push(&mut self, s: &str) -> Result<()> {
+        match self {
+            SharedStrings::Memory(v) => v.push(s.to_string()),
+            SharedStrings::Disk(d) => d.push(s)?,
+        };
+        Ok(())
+    }
+    fn len(&self) -> usize {
+        match self {
+            SharedStrings::Memory(v) => v.len(),
+            SharedStrings::Disk(d) => d.ends.len(),
+        }
+    }
+    /// get the cell value of the index, None if out of range
+    fn get_value(&self, inx: usize) -> Result<Option<CellValue<'_>>> {
+        match self {
+            SharedStrings::Memory(v) => Ok(v.get(inx).map(CellValue::Shared)),
+            SharedStrings::Disk(d) => Ok(d.get(inx)?.map(CellValue::String)),
+        }
+    }
+}
+
+static SPILL_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+/// unique path of a temporary file in dir (std::env::temp_dir() if None)
+pub(crate) fn temp_file_path(dir: Option<&Path>, ext: &str) -> PathBuf {
+    let dir = dir.map(Path::to_path_buf).unwrap_or_else(std::env::temp_dir);
+    dir.join(format!("xlsx_batch_reader_{}_{}.{}", std::process::id(), SPILL_SEQ.fetch_add(1, Ordering::Relaxed), ext))
+}
+
+/// temporary file removed on drop
+#[cfg(feature = "tokio")]
+pub(crate) struct TempFile(pub(crate) PathBuf);
+
+#[cfg(feature = "tokio")]
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// strings per block of the spilled shared strings
+const DISK_BLOCK_STRINGS: usize = 256;
+/// blocks cached in memory
+const DISK_CACHED_BLOCKS: usize = 16;
+/// blocks larger than it are not cached
+const DISK_MAX_BLOCK_BYTES: u64 = 1 << 20;
+
+/// file of the spilled shared strings with recently read blocks
+struct DiskCache {
+    file: Option<File>,
+    blocks: VecDeque<(usize, String)>,    //  块序号、块内容，最近使用的在前
+}
+
+/// shared strings in a temporary file: string blob + offset index in memory, the file will be removed on drop.   
+/// strings are read by blocks of DISK_BLOCK_STRINGS, and the recently used blocks are cached
+struct DiskStrings {
+    path: PathBuf,
+    ends: Vec<u64>,
+    writer: Option<BufWriter<File>>,
+    cache: Mutex<DiskCache>,
+}
+
+impl DiskStrings {
+    fn new(dir: Option<&Path>) -> Result<Self> {
+        let path = temp_file_path(dir, "sst");
+        let file = OpenOptions::new().read(true).write(true).create_new(true).open(&path)?;
+        let writer = BufWriter::new(file.try_clone()?);
+        Ok(Self { path, ends: Vec::new(), writer: Some(writer), cache: Mutex::new(DiskCache { file: Some(file), blocks: VecDeque::new() }) })
+    }
+    fn push(&mut self, s: &str) -> Result<()> {
+        let writer = self.writer.as_mut().ok_or_else(|| XlsxError::InvalidState("shared strings are finished".to_string()))?;
+        writer.write_all(s.as_bytes())?;
+        self.ends.push(self.ends.last().copied().unwrap_or(0) + s.len() as u64);
+        Ok(())
+    }
+    /// flush the written strings, no more strings can be pushed
+    fn finish(&mut self) -> Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+    /// start offset of the string
+    fn start_of(&self, inx: usize) -> u64 {
+        if inx == 0 {0} else {self.ends[inx-1]}
+    }
+    fn get(&self, inx: usize) -> Result<Option<String>> {
+        let Some(&end) = self.ends.get(inx) else {
+            return Ok(None);
+        };
+        let start = self.start_of(inx);
+        let block = inx / DISK_BLOCK_STRINGS;
+        let block_start = self.start_of(block * DISK_BLOCK_STRINGS);
+        let block_end = self.ends[std::cmp::min((block + 1) * DISK_BLOCK_STRINGS, self.ends.len()) - 1];
+        let mut guard = self.cache.lock().map_err(|_| XlsxError::InvalidState("shared strings file is poisoned".to_string()))?;
+        let cache = &mut *guard;
+        if let Some(pos) = cache.blocks.iter().position(|(b, _)| *b == block) {
+            if pos > 0 {
+                let hit = cache.blocks.remove(pos).unwrap_or_default();
+                cache.blocks.push_front(hit);
+            }
+        } else {
+            //  块过大时只读取单个字符串，不缓存
+            let single = block_end - block_start > DISK_MAX_BLOCK_BYTES;
+            let (from, to) = if single {(start, end)} else {(block_start, block_end)};
+            let mut buf = vec![0; (to-from) as usize];
+            if let Some(file) = cache.file.as_mut() {
+                file.seek(SeekFrom::Start(from))?;
+                file.read_exact(&mut buf)?;
+            }
+            if single {
+                return Ok(Some(String::from_utf8(buf)?));
+            }
+            cache.blocks.push_front((block, String::from_utf8(buf)?));
+            cache.blocks.truncate(DISK_CACHED_BLOCKS);
+        }
+        let text = &cache.blocks[0].1;
+        Ok(Some(text[(start-block_start) as usize..(end-block_start) as usize].to_string()))
+    }
+}
+
+impl Drop for DiskStrings {
+    fn drop(&mut self) {
+        self.writer.take();
+        if let Ok(mut guard) = self.cache.lock() {
+            guard.file.take();
+        }
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// reader of a part, which fails when the uncompressed size exceeds the limit
+struct LimitedReader<R> {
+    inner: R,
+    part: String,
+    size: u64,
+    limit: u64,
+}
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.size += n as u64;
+        if self.size > self.limit {
+            return Err(std::io::Error::other(LimitError(format!("{} exceeds the limit of {} uncompressed bytes", self.part, self.limit))));
+        }
+        Ok(n)
+    }
+}
+
+/// open a part of the zip archive, the uncompressed size is limited by ReadLimits
+fn open_part<'a>(zip_archive: &'a mut ZipArchive<BufReader<File>>, part: &str, limits: &ReadLimits) -> Result<LimitedReader<ZipFile<'a, BufReader<File>>>> {
+    match zip_archive.by_name(part) {
+        Ok(file) => {
+            let mut limit = limits.max_part_size.unwrap_or(u64::MAX);
+            if let Some(ratio) = limits.max_compression_ratio {
+                limit = std::cmp::min(limit, max(file.compressed_size(), 1).saturating_mul(ratio));
+            }
+            Ok(LimitedReader { inner: file, part: part.to_string(), size: 0, limit })
+        },
+        Err(ZipError::FileNotFound) => Err(XlsxError::MissingPart(part.to_string())),
+        Err(e) => Err(e.into())
+    }
+}
+
 /// xlsx book reader
 pub struct XlsxBook {
     ini_share: bool,
-    str_share: Vec<String>,
+    str_share: SharedStrings,
     shts_hidden: Vec<String>,
     shts_visible: Vec<String>,
     map_style: HashMap<u32, u32>,
     map_sheet: HashMap<String, String>,
     zip_archive: ZipArchive<BufReader<File>>,
     datetime_fmts: HashMap<u32, u8>,
+    dxfs: Vec<DxfStyle>,
+    cell_protections: Vec<CellProtection>,
+    book_protection: Option<WorkbookProtection>,
+    path: PathBuf,
+    options: ReadOptions,
+    warnings: Vec<String>,
+    #[cfg(feature = "tokio")]
+    pub(crate) temp_file: Option<TempFile>,    //  从AsyncRead打开时的临时文件，须在zip_archive之后释放
 }
 
 impl XlsxBook {
     /// load_share: if set to false, you should call load_share_strings before reading data. it should usually be true. If you only need to obtain the sheet names, you can set it false to open the file faster.
     pub fn new<T: AsRef<Path>>(path: T, load_share: bool) -> Result<XlsxBook> {
+        Self::new_with_options(path, load_share, ReadOptions::default())
+    }
+    /// the same as new, with options
+    pub fn new_with_options<T: AsRef<Path>>(path: T, load_share: bool, options: ReadOptions) -> Result<XlsxBook> {
         // zip压缩文件
+        let path = path.as_ref().to_path_buf();
         let mut zip_archive = {
-            let file = File::open(path)?;
+            let file = File::open(&path)?;
             let zipreader = BufReader::new(file);
             ZipArchive::new(zipreader)?
         };
 
         let book_refs = {
-            let file = zip_archive.by_name("xl/_rels/workbook.xml.rels")?;
+            let file = open_part(&mut zip_archive, "xl/_rels/workbook.xml.rels", &options.limits)?;
             
             let mut buf = Vec::new();
             let mut refs = HashMap::new();
             let mut reader =  Reader::from_reader(BufReader::new(file));
             loop {
                 match reader.read_event_into(&mut buf) {
-                    Ok(Event::Empty(ref e)) => {
-                        if e.name().as_ref() == b"Relationship"{
-                            refs.insert(get_attr_val!(e, "Id", to_string), get_attr_val!(e, "Target", to_string));
-                        };
-                    },
-                    Ok(Event::Start(ref e)) => {   // 解析 <sheet ..></sheet> 模式
-                        if e.name().as_ref() == b"Relationship"{
-                            refs.insert(get_attr_val!(e, "Id", to_string), get_attr_val!(e, "Target", to_string));
-                        };
+                    Ok(Event::Empty(ref e)) | Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"Relationship" => {
+                        refs.insert(get_attr_val!(e, "Id", to_string), get_attr_val!(e, "Target", to_string));
                     },
                     Ok(Event::Eof) => break, // exits the loop when reaching end of file
-                    Err(e) => return Err(anyhow!("workbook.xml.refs broken: {:?}", e)),
+                    Err(e) => return Err(XlsxError::from_xml("workbook.xml.rels", e)),
                     _ => ()                  // There are several other `Event`s we do not consider here
                 }
                 buf.clear();
@@ -117,67 +1010,45 @@ impl XlsxBook {
         // 初始化sheet列表
         let mut shts_hidden = Vec::<String>::new();
         let mut shts_visible = Vec::<String>::new();
+        let mut book_protection = None;
         let map_sheet = {
-            let file = zip_archive.by_name("xl/workbook.xml")?;
-            let mut reader =  Reader::from_reader(BufReader::new(file));
+            let file = open_part(&mut zip_archive, "xl/workbook.xml", &options.limits)?;
+            let mut reader =  NsReader::from_reader(BufReader::new(file));
             // reader.trim_text(true);
 
             let mut buf = Vec::new();
             let mut map_share: HashMap<String, String> = HashMap::new();
             loop {
                 match reader.read_event_into(&mut buf) {
-                    Ok(Event::Empty(ref e)) => {
-                        if e.name().as_ref() == b"sheet"{
-                            let name = get_attr_val!(e, "name", to_string);
-                            let rid = get_attr_val!(e, "r:id", to_string);
-                            let sheet = if book_refs.contains_key(&rid) {
-                                if book_refs[&rid].starts_with('/') {
-                                    format!("{}", book_refs[&rid].trim_start_matches('/'))
-                                } else {
-                                    format!("xl/{}", book_refs[&rid])
-                                }
-                            } else {
-                                return Err(anyhow!("Relationship of sheet-{rid} not found"))
-                            };
-                            match e.try_get_attribute("state").unwrap_or(None) {
-                                Some(attr) => {
-                                    if attr.unescape_value()?.as_bytes() == b"hidden" {
-                                        shts_hidden.push(name.clone());
-                                    } else {
-                                        shts_visible.push(name.clone());
-                                    };
-                                },
-                                _ => {shts_visible.push(name.clone());}
-                            };
-                            map_share.insert(name, sheet);  // sheet名，对应的真是xml文件
-                        };
+                    Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"workbookProtection" => {
+                        book_protection = Some(WorkbookProtection {
+                            lock_structure: attr_bool(e, "lockStructure", false)?,
+                            lock_windows: attr_bool(e, "lockWindows", false)?,
+                            lock_revision: attr_bool(e, "lockRevision", false)?,
+                            workbook_password: PasswordHash::from_attrs(e, "workbook")?,
+                            revisions_password: PasswordHash::from_attrs(e, "revisions")?,
+                        });
                     },
-                    Ok(Event::Start(ref e)) => {   // 解析 <sheet ..></sheet> 模式
-                        if e.name().as_ref() == b"sheet"{
-                            let name = get_attr_val!(e, "name", to_string);
-                            let rid = get_attr_val!(e, "r:id", to_string);
-                            let sheet = if book_refs.contains_key(&rid) {
-                                if book_refs[&rid].starts_with('/') {
-                                    format!("{}", book_refs[&rid].trim_start_matches('/'))
-                                } else {
-                                    format!("/xl/{}", book_refs[&rid])
-                                }
-                            } else {
-                                return Err(anyhow!("Relationship of sheet-rid not found!"))
-                            };
-                            match e.try_get_attribute("state").unwrap_or(None) {
-                                Some(attr) => {
-                                    if attr.unescape_value()?.as_bytes() != b"hidden" {
-                                        shts_visible.push(name.clone());
-                                    };
-                                },
-                                _ => {shts_visible.push(name.clone());}
-                            };
-                            map_share.insert(name, sheet);  // sheet名，对应的真是xml文件
+                    Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"sheet" => {   // 兼容 <sheet ..></sheet> 模式
+                        let name = get_attr_val!(e, "name", to_string);
+                        let rid = get_rel_attr(&reader, e, "id")?.unwrap_or_default();
+                        let sheet = match book_refs.get(&rid) {
+                            Some(target) => resolve_part("xl/workbook.xml", target),
+                            None => return Err(XlsxError::MalformedXml(format!("Relationship of sheet-{rid} not found")))
+                        };
+                        let hidden = match e.try_get_attribute("state").unwrap_or(None) {
+                            Some(attr) => attr.unescape_value()?.as_bytes() == b"hidden",
+                            None => false
                         };
+                        if hidden {
+                            shts_hidden.push(name.clone());
+                        } else {
+                            shts_visible.push(name.clone());
+                        };
+                        map_share.insert(name, sheet);  // sheet名，对应的真是xml文件
                     },
                     Ok(Event::Eof) => break, // exits the loop when reaching end of file
-                    Err(e) => return Err(anyhow!("workbook.xml is broken: {:?}", e)),
+                    Err(e) => return Err(XlsxError::from_xml("workbook.xml", e)),
                     _ => ()                  // There are several other `Event`s we do not consider here
                 }
                 buf.clear();
@@ -187,8 +1058,10 @@ impl XlsxBook {
 
         // 初始化单元格格式
         let mut datetime_fmts = DATETIME_FMTS.clone();
+        let mut dxfs = Vec::new();
+        let mut cell_protections = Vec::new();
         let map_style = {
-            match zip_archive.by_name("xl/styles.xml") {
+            match open_part(&mut zip_archive, "xl/styles.xml", &options.limits) {
                 Ok(file) => {
                     let mut reader =  Reader::from_reader(BufReader::new(file));
                     // reader.trim_text(true);
@@ -197,12 +1070,41 @@ impl XlsxBook {
                     let mut act = false;
                     let mut buf = Vec::new();
                     let mut map_style: HashMap<u32, u32> = HashMap::new();
+                    let mut dxf: Option<DxfStyle> = None;
+                    let mut dxf_part = 0;    //  1-font; 2-fill
                     loop {
                         match reader.read_event_into(&mut buf) {
+                            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if dxf.is_some() => {
+                                if let Some(d) = dxf.as_mut() {
+                                    let off = matches!(e.try_get_attribute("val")?, Some(v) if matches!(v.unescape_value()?.as_ref(), "0" | "false" | "none"));
+                                    match e.local_name().as_ref() {
+                                        b"font" => dxf_part = 1,
+                                        b"fill" => dxf_part = 2,
+                                        b"b" => d.bold = !off,
+                                        b"i" => d.italic = !off,
+                                        b"u" => d.underline = !off,
+                                        b"strike" => d.strike = !off,
+                                        b"color" if dxf_part == 1 => d.font_color = get_color_attr(e)?,
+                                        b"bgColor" if dxf_part == 2 => d.fill_color = get_color_attr(e)?,
+                                        b"fgColor" if dxf_part == 2 && d.fill_color.is_none() => d.fill_color = get_color_attr(e)?,
+                                        b"numFmt" => d.num_fmt = Some(get_attr_val!(e, "formatCode", to_string)),
+                                        _ => ()
+                                    }
+                                }
+                            },
+                            Ok(Event::End(ref e)) if dxf.is_some() => {
+                                match e.local_name().as_ref() {
+                                    b"font" | b"fill" => dxf_part = 0,
+                                    b"dxf" => dxfs.extend(dxf.take()),
+                                    _ => ()
+                                }
+                            },
+                            Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"dxf" => dxfs.push(DxfStyle::default()),
+                            Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"dxf" => dxf = Some(DxfStyle::default()),
                             Ok(Event::Start(ref e)) => {
-                                if e.name().as_ref() == b"cellXfs" || e.name().as_ref() == b"numFmts" {
+                                if e.local_name().as_ref() == b"cellXfs" || e.local_name().as_ref() == b"numFmts" {
                                     act = true;
-                                } else if act && (e.name().as_ref() == b"numFmt"){
+                                } else if act && (e.local_name().as_ref() == b"numFmt"){
                                     let code = get_attr_val!(e, "formatCode", to_string);
                                     if code.contains("yy") {
                                         if code.contains("h") || code.contains("ss") {
@@ -213,13 +1115,14 @@ impl XlsxBook {
                                     } else if code.contains("ss") {
                                         datetime_fmts.insert(get_attr_val!(e, "numFmtId", parse), FMT_TIME);
                                     };
-                                } else if act && (e.name().as_ref() == b"xf"){
+                                } else if act && (e.local_name().as_ref() == b"xf"){
                                     map_style.insert(inx, get_attr_val!(e, "numFmtId", parse));
+                                    cell_protections.push(CellProtection::default());
                                     inx += 1;
                                 };
                             },
                             Ok(Event::Empty(ref e)) => {
-                                if act && (e.name().as_ref() == b"numFmt"){
+                                if act && (e.local_name().as_ref() == b"numFmt"){
                                     let code = get_attr_val!(e, "formatCode", to_string);
                                     if code.contains("yy") {
                                         if code.contains("h") || code.contains("ss") {
@@ -230,20 +1133,25 @@ impl XlsxBook {
                                     } else if code.contains("ss") {
                                         datetime_fmts.insert(get_attr_val!(e, "numFmtId", parse), FMT_TIME);
                                     };
-                                } else if act && (e.name().as_ref() == b"xf"){
+                                } else if act && (e.local_name().as_ref() == b"xf"){
                                     map_style.insert(inx, get_attr_val!(e, "numFmtId", parse));
+                                    cell_protections.push(CellProtection::default());
                                     inx += 1;
+                                } else if act && (e.local_name().as_ref() == b"protection"){
+                                    if let Some(p) = cell_protections.last_mut() {
+                                        *p = CellProtection { locked: attr_bool(e, "locked", true)?, hidden: attr_bool(e, "hidden", false)? };
+                                    }
                                 };
                             },
                             Ok(Event::End(ref e)) => {
-                                if e.name().as_ref() == b"numFmts" {
+                                if e.local_name().as_ref() == b"numFmts" || e.local_name().as_ref() == b"cellXfs" {
                                     act = false;
-                                } else if e.name().as_ref() == b"cellXfs" {
+                                } else if e.local_name().as_ref() == b"dxfs" {
                                     break;
                                 };
                             },
                             Ok(Event::Eof) => break, // exits the loop when reaching end of file
-                            Err(e) => return Err(anyhow!("styles.xml is broken: {:?}", e)),
+                            Err(e) => return Err(XlsxError::from_xml("styles.xml", e)),
                             _ => ()                  // There are several other `Event`s we do not consider here
                         }
                         buf.clear();
@@ -258,13 +1166,21 @@ impl XlsxBook {
         
         let mut book = XlsxBook{
                 ini_share: false,
-                str_share: Vec::new(),
+                str_share: SharedStrings::Memory(Vec::new()),
                 map_style,
                 map_sheet,
                 shts_hidden,
                 shts_visible,
                 zip_archive,
                 datetime_fmts,
+                dxfs,
+                cell_protections,
+                book_protection,
+                path,
+                options,
+                warnings: Vec::new(),
+                #[cfg(feature = "tokio")]
+                temp_file: None,
             };
         if load_share {
             book.load_share_strings()?;
@@ -279,13 +1195,21 @@ impl XlsxBook {
     pub fn get_visible_sheets(&self) -> &Vec<String> {
         &self.shts_visible
     }
+    /// get warnings recorded in lenient mode (ReadOptions.strict is false)
+    pub fn get_warnings(&self) -> &Vec<String> {
+        &self.warnings
+    }
+    /// get differential formats (dxf) in styles.xml, which are referred by conditional formatting
+    pub fn get_dxf_styles(&self) -> &Vec<DxfStyle> {
+        &self.dxfs
+    }
     /// if set load_share to false, you should call load_share_strings before reading data
     pub fn load_share_strings(&mut self) -> Result<()>{
         if self.ini_share {
             return Ok(());
         };
         let str_share = {
-            match self.zip_archive.by_name("xl/sharedStrings.xml") {
+            match open_part(&mut self.zip_archive, "xl/sharedStrings.xml", &self.options.limits) {
                 Ok(file) => {
                     let mut reader =  Reader::from_reader(BufReader::new(file));
                     // reader.trim_text(true);
@@ -293,58 +1217,72 @@ impl XlsxBook {
                     let mut buf = Vec::with_capacity(3069);
                     let cap = loop {    // 获取ShareString容量
                         match reader.read_event_into(&mut buf) {
-                            Ok(Event::Start(ref e)) => {
-                                if e.name().as_ref() == b"sst"{
-                                    let cnt: usize =  {
-                                        match e.try_get_attribute("uniqueCount")? {
-                                            Some(a) => {a.unescape_value()?.parse()?},
-                                            None => {get_attr_val!(e, "count", parse)}
-                                        }
-                                    };
-                                    break cnt
-                                }
+                            Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"sst" => {
+                                let cnt: usize =  {
+                                    match e.try_get_attribute("uniqueCount")? {
+                                        Some(a) => {a.unescape_value()?.parse()?},
+                                        None => {get_attr_val!(e, "count", parse)}
+                                    }
+                                };
+                                break cnt
                             }
                             Ok(Event::Eof) => {return Ok(())}, // exits the loop when reaching end of file
-                            Err(e) => return Err(anyhow!("sharedStrings.xml is broken: {:?}", e)),
+                            Err(e) => return Err(XlsxError::from_xml("sharedStrings.xml", e)),
                             _ => (),                     // There are several other `Event`s we do not consider here
                         }
                     };
 
                     let mut insert = false;
                     let mut shstring = String::new(); 
-                    let mut vec_share: Vec<String> = Vec::with_capacity(cap);
+                    let limits = &self.options.limits;
+                    if let Some(n) = limits.max_shared_strings.filter(|n| cap > *n) {
+                        return Err(XlsxError::LimitExceeded(format!("sharedStrings.xml: {} strings exceed the limit of {}", cap, n)));
+                    }
+                    let mut vec_share = match self.options.shared_strings_spill {
+                        Some(n) if cap > n => SharedStrings::Disk(DiskStrings::new(self.options.spill_dir.as_deref())?),
+                        _ => SharedStrings::Memory(Vec::with_capacity(std::cmp::min(cap, MAX_RESERVED))),
+                    };
                     loop {
                         match reader.read_event_into(&mut buf) {
                             Ok(Event::Start(ref e)) => {
-                                match e.name().as_ref() {
+                                match e.local_name().as_ref() {
                                     b"si" => {shstring.clear()},
                                     b"t" => {insert = true},
                                     _ => {insert = false},
                                 }
                             },
-                            Ok(Event::Text(ref t)) => {
-                                if insert {
-                                    shstring += &String::from_utf8(t.to_vec())?;
+                            Ok(Event::Text(ref t)) if insert => {
+                                shstring += &String::from_utf8(t.to_vec())?;
+                                if let Some(n) = limits.max_cell_text.filter(|n| shstring.len() > *n) {
+                                    return Err(XlsxError::LimitExceeded(format!("sharedStrings.xml: string of {} bytes exceeds the limit of {}", shstring.len(), n)));
                                 }
                             },
-                            Ok(Event::End(ref e)) => {
-                                if e.name().as_ref() == b"si" {
-                                    vec_share.push(shstring.clone());
+                            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"si" => {
+                                //  uniqueCount可能与实际数量不符
+                                if let Some(n) = limits.max_shared_strings.filter(|n| vec_share.len() >= *n) {
+                                    return Err(XlsxError::LimitExceeded(format!("sharedStrings.xml: strings exceed the limit of {}", n)));
                                 }
+                                vec_share.push(&shstring)?;
                             },
                             Ok(Event::Eof) => break, // exits the loop when reaching end of file
-                            Err(e) => return Err(anyhow!("sharedStrings.xml is broken: {:?}", e)),
+                            Err(e) => return Err(XlsxError::from_xml("sharedStrings.xml", e)),
                             _ => ()                  // There are several other `Event`s we do not consider here
                         }
                         buf.clear();
                     };
                     if cap != vec_share.len() {  
-                        return Err(anyhow!("shareString-lenth check error!！"));
+                        if self.options.strict {
+                            return Err(XlsxError::MalformedXml("shareString-lenth check error!！".to_string()));
+                        }
+                        self.warnings.push(format!("sharedStrings.xml: uniqueCount is {}, but {} strings found", cap, vec_share.len()));
                     };
+                    if let SharedStrings::Disk(d) = &mut vec_share {
+                        d.finish()?;
+                    }
                     vec_share
                 },
                 Err(_) => {
-                    Vec::<String>::new()
+                    SharedStrings::Memory(Vec::new())
                 }
             }
         };
@@ -364,7 +1302,7 @@ impl XlsxBook {
                     self.load_share_strings()?;
                 };
 
-                match self.zip_archive.by_name(v.as_str()) {
+                match open_part(&mut self.zip_archive, v.as_str(), &self.options.limits) {
                     Ok(file) => {
                         let reader = Reader::from_reader(BufReader::new(file));
                         // reader.trim_text(true);
@@ -376,7 +1314,30 @@ impl XlsxBook {
                             right_ncol,
                             iter_batch,
                             first_row_is_header,
+                            returned_row: 0,
+                            rows_processed: 0,
+                            progress: None,
+                            cancel_token: None,
+                            scratch: RowScratch::default(),
+                            parallel: 0,
+                            chunk_size: PARALLEL_CHUNK_SIZE,
+                            parsed_rows: VecDeque::new(),
+                            raw_tail: Vec::new(),
                             first_row: None,
+                            header_nrows: 1,
+                            header_rows: Vec::new(),
+                            header_names: None,
+                            pending_rows: VecDeque::new(),
+                            fill_merged: false,
+                            merged_tops: HashMap::new(),
+                            sheet_tail: None,
+                            layout_rows: false,
+                            validations: None,
+                            violations: Vec::new(),
+                            options: self.options.clone(),
+                            warnings: Vec::new(),
+                            book_path: &self.path,
+                            sheet_part: v,
                             key: k,
                             buf: Vec::with_capacity(8*1024),
                             status: 1,
@@ -384,24 +1345,35 @@ impl XlsxBook {
                             str_share: &self.str_share,
                             map_style: &self.map_style,
                             datetime_fmts: &self.datetime_fmts,
+                            dxfs: &self.dxfs,
                             max_size: None,
                             merged_rects: None,
                             skip_until: None,
                             skip_matched: None,
                             skip_matched_check_by_and: true,
                             read_before: None,
+                            skip_until_matched: false,
+                            read_before_matched: false,
                             header_check: None,
                             addr_captures: None,
                             vals_captures: HashMap::new(),
                         });
                     },
                     Err(_) => {
-                        return Err(anyhow!("sheet {} - {} lost！", k.as_str(), v.as_str()));
+                        return Err(XlsxError::MissingPart(v));
                     }
                 };
             };
         };
-        Err(anyhow!(format!("{} sheet not found!", sht_name)))
+        Err(XlsxError::SheetNotFound(sht_name.to_string()))
+    }
+    /// reopen the sheet of the checkpoint, positioned after the checkpoint row. the header, captured values and the matched status of
+    /// with_skip_until and with_read_before are restored, other options (e.g. with_skip_matched, with_fill_merged) should be set again on the returned sheet
+    pub fn resume_sheet(&mut self, checkpoint: &SheetCheckpoint, iter_batch: usize) -> Result<XlsxSheet<'_>> {
+        let (left_ncol, right_ncol) = checkpoint.column_range;
+        let mut sheet = self.get_sheet_by_name(&checkpoint.sheet, iter_batch, 0, left_ncol, right_ncol, false)?;
+        sheet.with_checkpoint(checkpoint)?;
+        Ok(sheet)
     }
     /// get cached sheet by name, all data will be cached in memory when sheet created
     #[cfg(feature = "cached")]
@@ -412,56 +1384,305 @@ impl XlsxBook {
     pub fn get_sheets_maps(&self) -> &HashMap<String, String> {
         &self.map_sheet
     }
+    /// read elements referring parts in workbook.xml (e.g. pivotCache, externalReference) in order, returns (value of attr, part)
+    fn read_workbook_refs(&mut self, tag: &[u8], attr: Option<&str>) -> Result<Vec<(Option<String>, Option<String>)>> {
+        let limits = &self.options.limits;
+        let book_rels = read_part_rels(&mut self.zip_archive, "xl/workbook.xml", limits)?;
+        let file = open_part(&mut self.zip_archive, "xl/workbook.xml", limits)?;
+        let mut reader = NsReader::from_reader(BufReader::new(file));
+        let mut buf = Vec::new();
+        let mut refs = Vec::new();
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if e.local_name().as_ref() == tag => {
+                    //  缺少关系的元素也保留位置，part为None
+                    let part = get_rel_attr(&reader, e, "id")?.and_then(|rid| book_rels.get(&rid)).map(|rel| resolve_part("xl/workbook.xml", &rel.target));
+                    let val = match attr.map(|v| e.try_get_attribute(v)).transpose()?.flatten() {
+                        Some(v) => Some(v.unescape_value()?.to_string()),
+                        None => None
+                    };
+                    refs.push((val, part));
+                },
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(XlsxError::from_xml("xl/workbook.xml", e)),
+                _ => ()
+            }
+            buf.clear();
+        }
+        Ok(refs)
+    }
+    /// check whether the book contains macros: VBA project (xl/vbaProject.bin) or Excel 4.0 macro sheets
+    pub fn has_macros(&self) -> bool {
+        self.zip_archive.file_names().any(|name| {
+            let name = name.trim_start_matches('/').to_ascii_lowercase();
+            name == "xl/vbaproject.bin" || name.starts_with("xl/macrosheets/")
+        })
+    }
+    /// get VBA project with module names and source code, None if the book has no VBA project
+    #[cfg(feature = "vba")]
+    pub fn get_vba_project(&mut self) -> Result<Option<VbaProject>> {
+        let mut data = Vec::new();
+        match open_part(&mut self.zip_archive, "xl/vbaProject.bin", &self.options.limits) {
+            Ok(mut file) => file.read_to_end(&mut data)?,
+            Err(XlsxError::MissingPart(_)) => return Ok(None),
+            Err(e) => return Err(e)
+        };
+        Ok(Some(vba::parse_vba_project(&data)?))
+    }
+}
+
+/// bytes decompressed for each thread in a wave of parallel parsing
+const PARALLEL_CHUNK_SIZE: usize = 4 << 20;
+
+/// context of parsing the cells, shared by XlsxSheet::parse_row and parse_chunk
+struct CellCtx<'a, 'b> {
+    key: &'b str,
+    str_share: &'a SharedStrings,
+    map_style: &'b HashMap<u32, u32>,
+    datetime_fmts: &'b HashMap<u32, u8>,
+    options: &'b ReadOptions,
+}
+
+/// context of parsing a chunk of rows in parallel
+struct ChunkCtx<'a, 'b> {
+    cells: CellCtx<'a, 'b>,
+    skip_rows: u32,
+    left_ncol: ColNum,
+    right_ncol: ColNum,
+    cancel_token: Option<&'b CancelToken>,
+}
+
+/// rows parsed from a chunk
+#[derive(Default)]
+struct ChunkRows<'a> {
+    rows: Vec<(u32, Vec<CellValue<'a>>)>,
+    warnings: Vec<String>,
+    processed: usize,
+    end: bool,    //  读取到sheetData结尾，或宽松模式下xml损坏
+}
+
+/// bytes of a row start tag, with or without the namespace prefix (e.g. `<x:row`)
+fn is_row_start(bytes: &[u8]) -> bool {
+    let Some(tag) = bytes.strip_prefix(b"<") else {
+        return false;
+    };
+    let prefix = tag.iter().take(32).position(|v| *v == b':').filter(|n| tag[..*n].iter().all(|v| v.is_ascii_alphanumeric() || *v == b'_' || *v == b'-'));
+    let tag = prefix.map(|n| &tag[n+1..]).unwrap_or(tag);
+    tag.starts_with(b"row") && matches!(tag.get(3), Some(b' ' | b'>' | b'/' | b'\t' | b'\r' | b'\n'))
+}
+
+/// find the first row start tag at or after pos
+fn find_row_start(bytes: &[u8], pos: usize) -> Option<usize> {
+    (pos..bytes.len().saturating_sub(4)).find(|i| bytes[*i] == b'<' && is_row_start(&bytes[*i..]))
+}
+
+/// parse a chunk of rows (starting at a row boundary), the same as XlsxSheet::parse_row except the filters evaluated in order
+fn parse_chunk<'a>(chunk: &[u8], ctx: &ChunkCtx<'a, '_>) -> Result<ChunkRows<'a>> {
+    let mut reader = Reader::from_reader(chunk);
+    reader.config_mut().allow_unmatched_ends = true;    //  块内的结束标签可能没有对应的开始标签
+    let mut buf = Vec::with_capacity(8*1024);
+    let mut out = ChunkRows::default();
+    let mut sc = RowScratch::default();
+    let mut row_value: Vec<CellValue<'a>> = Vec::new();
+    let (mut currow, mut col, mut col_index, mut num_fmt_id, mut get_cell): (RowNum, ColNum, ColNum, u32, bool) = (0, 0, 1, 0, false);
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) => {
+                sc.prev_head.clear();
+                sc.prev_head.extend_from_slice(e.local_name().as_ref());
+                if sc.prev_head == b"c" {
+                    (col, num_fmt_id) = sc.start_cell(e, &ctx.cells, currow, &mut out.warnings)?;
+                    get_cell = currow > ctx.skip_rows && col > ctx.left_ncol && col <= ctx.right_ncol;
+                } else if sc.prev_head == b"row" {
+                    currow = get_attr_val!(e, "r", parse);
+                    if let Some(n) = ctx.cells.options.limits.max_rows.filter(|n| currow > *n) {
+                        return Err(XlsxError::LimitExceeded(format!("{}: row {} exceeds the limit of {}", ctx.cells.key, currow, n)));
+                    }
+                    row_value = Vec::new();
+                    col_index = 1;
+                }
+            },
+            Ok(Event::Text(ref t)) if get_cell && (sc.prev_head == b"v" || sc.prev_head == b"t") => {
+                let cel_val = sc.cell_value(t, &ctx.cells, num_fmt_id, currow, col, &mut out.warnings)?;
+                while col_index + ctx.left_ncol < col {
+                    row_value.push(CellValue::Blank);
+                    col_index += 1;
+                }
+                col_index += 1;
+                row_value.push(cel_val);
+            },
+            Ok(Event::End(ref e)) => {
+                if e.local_name().as_ref() == b"row" {
+                    out.processed += 1;
+                    if !row_value.is_empty() {
+                        out.rows.push((currow, std::mem::take(&mut row_value)));
+                    }
+                    if ctx.cancel_token.map(|t| t.is_cancelled()).unwrap_or(false) {
+                        return Err(XlsxError::Cancelled);
+                    }
+                } else if e.local_name().as_ref() == b"sheetData" {
+                    out.end = true;
+                    break;
+                }
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                let err = XlsxError::from_xml("sheet data", e);
+                if ctx.cells.options.strict || matches!(err, XlsxError::LimitExceeded(_)) {
+                    return Err(err);
+                }
+                //  宽松模式下，xml损坏时结束读取
+                out.warnings.push(format!("row {}: {}", currow, err));
+                out.end = true;
+                break;
+            },
+            _ => ()
+        }
+        buf.clear();
+    }
+    Ok(out)
+}
+
+/// result of filtering a row
+enum RowAction {
+    Accept,
+    Skip,
+    Stop,
+}
+
+/// reusable buffers of the row parser
+#[derive(Default)]
+struct RowScratch {
+    cell_addr: String,
+    cell_type: Vec<u8>,
+    prev_head: Vec<u8>,
+}
+
+impl RowScratch {
+    /// read the type, number format and address of a cell start tag, returns (col, num_fmt_id)
+    fn start_cell(&mut self, e: &BytesStart<'_>, ctx: &CellCtx, currow: RowNum, warnings: &mut Vec<String>) -> Result<(ColNum, u32)> {
+        self.cell_type.clear();
+        match e.try_get_attribute("t")? {
+            Some(attr) => self.cell_type.extend_from_slice(attr.unescape_value()?.as_bytes()),
+            None => self.cell_type.extend_from_slice(b"n")
+        };
+        let num_fmt_id = match e.try_get_attribute("s")? {
+            Some(attr) => {
+                let style: u32 = attr.unescape_value()?.parse()?;
+                match ctx.map_style.get(&style) {
+                    Some(v) => *v,
+                    None if ctx.options.strict => {
+                        return Err(XlsxError::MalformedXml(format!("style index {} not found", style)));
+                    },
+                    None => {
+                        warnings.push(format!("row {}: style index {} not found", currow, style));
+                        0
+                    }
+                }
+            },
+            None => 0
+        };
+        self.cell_addr.clear();
+        self.cell_addr.push_str(&get_attr_val!(e, "r"));   //  单元格地址
+        let col = get_num_from_ord(self.cell_addr.as_bytes()).unwrap_or(0);
+        if let Some(n) = ctx.options.limits.max_cols.filter(|n| col > *n) {
+            return Err(XlsxError::LimitExceeded(format!("{}!{}: column exceeds the limit of {}", ctx.key, self.cell_addr, n)));
+        }
+        Ok((col, num_fmt_id))
+    }
+    /// parse the text of the v or t element into the value of the current cell
+    fn cell_value<'a>(&self, t: &[u8], ctx: &CellCtx<'a, '_>, num_fmt_id: u32, currow: RowNum, col: ColNum, warnings: &mut Vec<String>) -> Result<CellValue<'a>> {
+        if let Some(n) = ctx.options.limits.max_cell_text.filter(|n| t.len() > *n) {
+            return Err(XlsxError::LimitExceeded(format!("{}!{}: text of {} bytes exceeds the limit of {}", ctx.key, self.cell_addr, t.len(), n)));
+        }
+        match parse_cell_value(t, &self.cell_type, &self.prev_head, num_fmt_id, ctx.str_share, ctx.datetime_fmts) {
+            Ok(v) => Ok(v),
+            Err(e) if ctx.options.strict => Err(e.with_location(ctx.key, currow, col)),
+            Err(e) => {
+                //  宽松模式下，无法解析的值记为错误值
+                warnings.push(e.with_location(ctx.key, currow, col).to_string());
+                Ok(CellValue::Error(String::from_utf8_lossy(t).to_string()))
+            }
+        }
+    }
 }
 
 /// batch sheet reader
 pub struct XlsxSheet<'a> {
     key: String,
-    str_share: &'a Vec<String>,
+    str_share: &'a SharedStrings,
     map_style: &'a HashMap<u32, u32>,
     buf: Vec<u8>,
-    status: u8,   // 0-closed; 1-new; 2-active; 3-get_cell; 4-skip_cell; 初始为1
+    status: u8,   // 0-closed; 1-new; 2-active; 3-get_cell; 4-skip_cell; 5-get_merged_cell; 初始为1
     currow: RowNum,  //  当前行号
-    reader: Reader<BufReader<ZipFile<'a, BufReader<File>>>>,
+    reader: Reader<BufReader<LimitedReader<ZipFile<'a, BufReader<File>>>>>,
     iter_batch: usize,
     skip_rows: u32,
     max_size: Option<(RowNum, ColNum)>,
     left_ncol: ColNum,
     right_ncol: ColNum,
     first_row_is_header: bool,    //  标识是否需要把读取到的第一行作为标题，读取到标题行以后，会被设置为false
+    returned_row: RowNum,    //  已返回给调用方的最后一行
+    rows_processed: usize,
+    progress: Option<(usize, ProgressFn<'a>)>,    //  每处理n行回调一次
+    cancel_token: Option<CancelToken>,
+    scratch: RowScratch,
+    parallel: usize,    //  并行解析的线程数，0表示不启用
+    chunk_size: usize,    //  并行解析时，每个线程每批解压的字节数
+    parsed_rows: VecDeque<(u32, Vec<CellValue<'a>>)>,    //  并行解析、尚未过滤的行
+    raw_tail: Vec<u8>,    //  并行解析时，未解析完整行的字节
     first_row: Option<(u32, Vec<CellValue<'a>>)>,
+    header_nrows: usize,    //  标题行数，缺省为1
+    header_rows: Vec<(u32, Vec<CellValue<'a>>)>,
+    header_names: Option<Vec<String>>,
+    pending_rows: VecDeque<(u32, Vec<CellValue<'a>>)>,    //  自动识别标题行时已读取、尚未返回的数据行
+    fill_merged: bool,
+    merged_tops: HashMap<(RowNum, ColNum), Option<CellValue<'a>>>,    //  合并区域左上角单元格的值
+    sheet_tail: Option<SheetTail>,
+    layout_rows: bool,    //  sheet_tail中是否包含行高
+    validations: Option<Vec<DataValidation>>,    //  需检查的数据验证规则
+    violations: Vec<ValidationViolation>,
+    options: ReadOptions,
+    warnings: Vec<String>,
+    book_path: &'a Path,
+    sheet_part: String,
     datetime_fmts: &'a HashMap<u32, u8>,
-    merged_rects: Option<Vec<((RowNum, ColNum), (RowNum, ColNum))>>,
+    dxfs: &'a Vec<DxfStyle>,
+    merged_rects: Option<MergedRanges>,
     skip_until: Option<HashMap<usize, HashSet<String>>>,
     skip_matched: Option<HashMap<usize, HashSet<String>>>,
     skip_matched_check_by_and: bool,
     read_before: Option<HashMap<usize, HashSet<String>>>,
+    skip_until_matched: bool,    //  with_skip_until的行已匹配（含从检查点恢复）
+    read_before_matched: bool,    //  with_read_before的行已匹配（含从检查点恢复），不再返回数据
     header_check: Option<HashMap<usize, HashSet<String>>>,
     addr_captures: Option<HashSet<String>>,
     vals_captures: HashMap<String, CellValue<'a>>
 }
 
 impl<'a> XlsxSheet<'a> {
-    /// into cached sheet
+    /// into cached sheet, all remaining data will be cached in memory. options set by with_* functions (e.g. with_header_rows) take effect
     #[cfg(feature = "cached")]
-    fn into_cached_sheet(mut self) -> Result<CachedSheet<'a>> {
-        let top_nrow = if self.first_row_is_header {self.skip_rows+2} else {self.skip_rows+1};
+    pub fn into_cached_sheet(mut self) -> Result<CachedSheet<'a>> {
+        let top_nrow = if self.first_row_is_header {self.skip_rows+1+self.header_nrows as u32} else {self.skip_rows+1};
         if self.first_row_is_header {
             self.get_header_row()?;
         }
         let (data, bottom_nrow) =  match self.get_next_row() {
             Ok(Some((r, d))) => {
                 let mut data = if let Some((rn, _)) = self.max_size {
-                    HashMap::with_capacity(rn as usize)
+                    HashMap::with_capacity(std::cmp::min(rn as usize, MAX_RESERVED))
                 } else {
                     HashMap::new()
                 };
+                self.validate_row(r, &d);
                 data.insert(r, d);
                 let mut last_nrow = r;
                 loop {
                     match self.get_next_row() {
                         Ok(Some((r, d))) => {
                             last_nrow = r;
+                            self.validate_row(r, &d);
                             data.insert(r, d);
                         },
                         Ok(None) => {
@@ -478,6 +1699,7 @@ impl<'a> XlsxSheet<'a> {
             Err(e) => {return Err(e);}
         };
         let merged_rects = self.get_merged_ranges()?.to_owned();
+        let merged_tops = std::mem::take(&mut self.merged_tops).into_iter().filter_map(|(k, v)| v.map(|v| (k, v))).collect();
         let right_ncol = if self.right_ncol == MAX_COL_NUM {
             if let Some((_mr, mc)) = self.max_size {
                 mc
@@ -501,14 +1723,122 @@ impl<'a> XlsxSheet<'a> {
             left_ncol: self.left_ncol + 1,
             right_ncol,
             header_row: self.first_row,
+            header_names: self.header_names,
+            warnings: self.warnings,
+            sheet_tail: self.sheet_tail,
+            violations: self.violations,
+            merged_tops,
+            tops_captured: self.fill_merged,
         })
     }
-    /// get sheet name
-    pub fn sheet_name(&self) -> &String {
-        &self.key
+    /// get sheet name
+    pub fn sheet_name(&self) -> &String {
+        &self.key
+    }
+    /// get warnings recorded in lenient mode (ReadOptions.strict is false)
+    pub fn get_warnings(&self) -> &Vec<String> {
+        &self.warnings
+    }
+    /// call the callback every n rows processed and when the sheet data ends, n = 0 means only when the sheet data ends. in parallel mode, it is called after each wave of chunks parsed
+    pub fn with_progress<F: FnMut(&ReadProgress) + Send + 'a>(&mut self, every_rows: usize, callback: F) {
+        self.progress = Some((every_rows, Box::new(callback)));
+    }
+    /// cancel reading by the token, checked between rows
+    pub fn with_cancel_token(&mut self, token: CancelToken) {
+        self.cancel_token = Some(token);
+    }
+    /// get current progress
+    pub fn get_progress(&self) -> ReadProgress {
+        let part = self.reader.get_ref().get_ref();
+        let (total_bytes, compressed_size) = (part.inner.size(), part.inner.compressed_size());
+        let compressed_read = if total_bytes == 0 {
+            compressed_size
+        } else {
+            (part.size as f64 / total_bytes as f64 * compressed_size as f64) as u64
+        };
+        ReadProgress {
+            rows: self.rows_processed,
+            current_row: self.currow,
+            total_rows: self.max_size.map(|v| v.0),
+            bytes_read: part.size,
+            total_bytes,
+            compressed_read: std::cmp::min(compressed_read, compressed_size),
+            compressed_size,
+        }
+    }
+    /// report progress to the callback
+    fn report_progress(&mut self, force: bool) {
+        if let Some((n, _)) = &self.progress {
+            if force || self.rows_processed.checked_rem(*n) == Some(0) {    //  n为0时不按行数报告
+                let progress = self.get_progress();
+                if let Some((_, callback)) = &mut self.progress {
+                    callback(&progress);
+                }
+            }
+        }
+    }
+    /// get the checkpoint after the rows returned, which can be used to resume reading by XlsxBook::resume_sheet
+    pub fn checkpoint(&self) -> SheetCheckpoint {
+        let header_rows: Vec<(RowNum, Vec<CellValue<'static>>)> = self.header_rows.iter()
+            .map(|(r, cells)| (*r, cells.iter().map(|c| c.clone().into_owned()).collect()))
+            .collect();
+        let header_nrows = if self.first_row_is_header {
+            self.header_nrows
+        } else {
+            header_rows.len()
+        };
+        let header_row = if self.first_row_is_header {0} else {header_rows.last().map(|v| v.0).unwrap_or(0)};
+        SheetCheckpoint {
+            sheet: self.key.clone(),
+            row: max(self.skip_rows, max(self.returned_row, header_row)),
+            column_range: self.column_range(),
+            header_rows: if self.first_row_is_header {Vec::new()} else {header_rows},
+            header_nrows,
+            header_names: self.header_names.clone(),
+            captured_vals: self.vals_captures.iter().map(|(k, v)| (k.clone(), v.clone().into_owned())).collect(),
+            skip_until_matched: self.skip_until.is_none(),
+            read_before_matched: self.read_before_matched,
+        }
+    }
+    /// restore the state of the checkpoint, rows before the checkpoint row (included) will be skipped, usually called by XlsxBook::resume_sheet.   
+    /// this function should be called before reading, other with_* functions can be called before or after it,
+    /// but with_skip_until and with_read_before take no effect if their rows have been matched before the checkpoint
+    pub fn with_checkpoint(&mut self, checkpoint: &SheetCheckpoint) -> Result<()> {
+        if self.currow > 0 {
+            return Err(XlsxError::InvalidState("with_checkpoint should be called before reading".to_string()));
+        }
+        if checkpoint.sheet != self.key || checkpoint.column_range != self.column_range() {
+            return Err(XlsxError::InvalidState(format!("checkpoint of {} does not match sheet {}", checkpoint.sheet, self.key)));
+        }
+        self.skip_rows = checkpoint.row;
+        self.returned_row = checkpoint.row;
+        if checkpoint.skip_until_matched {
+            self.skip_until = None;
+            self.skip_until_matched = true;
+        }
+        if checkpoint.read_before_matched {
+            self.read_before = None;
+            self.read_before_matched = true;
+        }
+        if let Some((r, cells)) = checkpoint.header_rows.last() {
+            self.header_rows = checkpoint.header_rows.clone();
+            self.header_nrows = checkpoint.header_rows.len();
+            self.first_row = Some((*r, cells.clone()));
+            self.header_names = checkpoint.header_names.clone();
+            self.first_row_is_header = false;
+            self.addr_captures = None;   //  标题行已读取，不再匹配captures
+        } else {
+            self.first_row_is_header = checkpoint.header_nrows > 0;
+            self.header_nrows = max(checkpoint.header_nrows, 1);
+        }
+        self.vals_captures.extend(checkpoint.captured_vals.clone());
+        Ok(())
     }
     /// skip until a row matched，this function should be called before reading(the matched row will be returned)   
     pub fn with_skip_until(&mut self, checks: &HashMap<String, String>) {
+        if self.skip_until_matched {
+            return;    //  检查点之前已匹配
+        }
         let mut maps = HashMap::new();
         for (c, v) in checks {
             let col = get_num_from_ord(c.as_bytes()).unwrap_or(0);
@@ -542,6 +1872,9 @@ impl<'a> XlsxSheet<'a> {
     }
     /// read before a row matched，this function should be called before reading(the matched row will not be returned)
     pub fn with_read_before(&mut self, checks: &HashMap<String, String>) {
+        if self.read_before_matched {
+            return;    //  检查点之前已匹配
+        }
         let mut maps = HashMap::new();
         for (c, v) in checks {
             let col = get_num_from_ord(c.as_bytes()).unwrap_or(0);
@@ -570,6 +1903,197 @@ impl<'a> XlsxSheet<'a> {
             self.header_check = None;
         }
     }
+    /// read n header rows instead of one, this function should be called before reading.   
+    /// the group captions of upper rows (e.g. "Q1" spanning "Revenue | Cost") are flattened into column names like `Q1.Revenue` by the merged ranges, see get_header_names.   
+    /// if n > 1, the merged ranges are scanned ahead of sheet data (see with_prescan). n = 0 means no header row
+    pub fn with_header_rows(&mut self, n: usize) -> Result<()> {
+        if n > 1 && self.merged_rects.is_none() {
+            self.with_prescan()?;
+        }
+        self.first_row_is_header = n > 0;
+        self.header_nrows = max(n, 1);
+        Ok(())
+    }
+    /// detect the header row among the first max_rows rows automatically, this function should be called before reading.   
+    /// the header row is the densest row of distinct non-numeric strings followed by consistent data rows.   
+    /// returns the row number of the header row and the confidence (0.0 ~ 1.0), then data will be read after it.   
+    /// the non-blank cells above the header row (e.g. titles and notes) can be obtained by get_captured_vals.   
+    /// if with_header_rows(n) is set, the detected row is taken as the last header row
+    pub fn detect_header_row(&mut self, max_rows: usize) -> Result<Option<(RowNum, f64)>> {
+        if self.first_row.is_some() || !self.header_rows.is_empty() || self.currow > 0 {
+            return Err(XlsxError::InvalidState("detect_header_row should be called before reading".to_string()));
+        }
+        let first_row_is_header = self.first_row_is_header;
+        self.first_row_is_header = true;   //  扫描期间不跳过匹配行
+        let captures = self.addr_captures.take();
+        let mut scanned = Vec::with_capacity(max_rows);
+        while scanned.len() < max_rows {
+            match self.get_next_row()? {
+                Some(v) => scanned.push(v),
+                None => break
+            }
+        }
+        if scanned.is_empty() {
+            //  未扫描到行时恢复原有设置
+            self.first_row_is_header = first_row_is_header;
+            self.addr_captures = captures;
+            return Ok(None);
+        }
+        let (inx, confidence) = guess_header_row(&scanned);
+        let start = (inx + 1).saturating_sub(self.header_nrows);
+        let mut rest = scanned.split_off(inx + 1);
+        for (i, (row, cells)) in scanned.iter().enumerate() {
+            for (j, cell) in cells.iter().enumerate() {
+                let addr = get_ord_from_tuple(*row, self.left_ncol + j as ColNum + 1)?;
+                let captured = captures.as_ref().map(|v| v.contains(&addr)).unwrap_or(false);
+                if captured || (i < start && !matches!(cell, CellValue::Blank)) {
+                    self.vals_captures.insert(addr, cell.clone());
+                }
+            }
+        }
+        if let Some(skip_matched) = &self.skip_matched {
+            rest.retain(|(_, r)| !is_matched_row(r, skip_matched, self.skip_matched_check_by_and).0);
+        }
+        let row = scanned[inx].0;
+        self.pending_rows.extend(rest);
+        self.header_rows = scanned.split_off(start);
+        self.header_nrows = self.header_rows.len();
+        self.get_header_row()?;
+        Ok(Some((row, confidence)))
+    }
+    /// scan the trailing elements (merged ranges, hyperlinks, data validations, conditional formatting, protection) and the layout ahead of sheet data, at the cost of one extra decompression pass.   
+    /// after that, get_merged_ranges can be called before data fetched. the getters of trailing elements below call it on demand
+    pub fn with_prescan(&mut self) -> Result<&SheetTail> {
+        if self.sheet_tail.is_none() {
+            self.prescan(false)?;
+        }
+        self.get_sheet_tail().ok_or(XlsxError::InvalidState("sheet tail error".to_string()))
+    }
+    fn prescan(&mut self, with_rows: bool) -> Result<()> {
+        let mut tail = scan_sheet_tail(self.book_path, &self.sheet_part, &self.options, with_rows, &mut self.warnings)?;
+        for rule in tail.conditional_formats.iter_mut().flat_map(|cf| cf.rules.iter_mut()) {
+            rule.dxf = rule.dxf_id.and_then(|i| self.dxfs.get(i as usize).cloned());
+        }
+        self.merged_rects = Some(tail.merged_ranges.clone());
+        self.sheet_tail = Some(tail);
+        self.layout_rows = with_rows;
+        Ok(())
+    }
+    /// get trailing elements of the sheet, None if with_prescan is not called
+    pub fn get_sheet_tail(&self) -> Option<&SheetTail> {
+        self.sheet_tail.as_ref()
+    }
+    /// get data validation rules of the sheet
+    pub fn get_data_validations(&mut self) -> Result<&Vec<DataValidation>> {
+        Ok(&self.with_prescan()?.data_validations)
+    }
+    /// get layout of the sheet (auto filter, pane, column widths and row heights).   
+    /// row heights are not collected by with_prescan, the sheet is scanned again if it is prescanned already
+    pub fn get_layout(&mut self) -> Result<&SheetLayout> {
+        match self.sheet_tail.as_mut() {
+            Some(tail) if !self.layout_rows => {
+                //  警告已在预扫描时记录
+                tail.layout.rows = scan_sheet_tail(self.book_path, &self.sheet_part, &self.options, true, &mut Vec::new())?.layout.rows;
+                self.layout_rows = true;
+            },
+            None => self.prescan(true)?,
+            _ => ()
+        }
+        Ok(&self.with_prescan()?.layout)
+    }
+    /// get conditional formatting of the sheet
+    pub fn get_conditional_formats(&mut self) -> Result<&Vec<ConditionalFormat>> {
+        Ok(&self.with_prescan()?.conditional_formats)
+    }
+    /// get protection of the sheet, None if not set
+    pub fn get_protection(&mut self) -> Result<Option<&SheetProtection>> {
+        Ok(self.with_prescan()?.protection.as_ref())
+    }
+    /// get ranges allowed to edit when the sheet is protected
+    pub fn get_protected_ranges(&mut self) -> Result<&Vec<ProtectedRange>> {
+        Ok(&self.with_prescan()?.protected_ranges)
+    }
+    /// check every returned cell against its data validation rule, this function should be called before reading.   
+    /// header rows are not checked, violations can be obtained by get_violations
+    pub fn with_validation(&mut self, validate: bool) -> Result<()> {
+        if validate && self.currow > 0 {
+            return Err(XlsxError::InvalidState("with_validation should be called before reading".to_string()));
+        }
+        self.validations = if validate {
+            let rules = self.with_prescan()?.data_validations.iter().filter(|v| !matches!(v.kind, ValidationKind::Any | ValidationKind::Custom)).cloned().collect();
+            Some(rules)
+        } else {
+            None
+        };
+        Ok(())
+    }
+    /// get violations of data validation rules found so far, with_validation must be called before reading
+    pub fn get_violations(&self) -> &Vec<ValidationViolation> {
+        &self.violations
+    }
+    /// check a returned row against the data validation rules
+    fn validate_row(&mut self, row: RowNum, row_value: &[CellValue<'a>]) {
+        let Some(rules) = &self.validations else {
+            return;
+        };
+        let left = self.left_ncol;
+        for rule in rules {
+            for ((r1, c1), (r2, c2)) in rule.ranges.iter() {
+                if row < *r1 || row > *r2 {
+                    continue;
+                }
+                //  只检查读取范围内的列
+                let start = max(*c1, left+1);
+                let end = std::cmp::min(*c2, left+row_value.len() as ColNum);
+                for col in start..=end {
+                    let val = &row_value[(col-left-1) as usize];
+                    if let Some(reason) = rule.check(val) {
+                        self.violations.push(ValidationViolation { row, col, value: val.clone().into_owned(), sqref: rule.sqref.clone(), reason });
+                    }
+                }
+            }
+        }
+    }
+    /// parse the sheet data on threads (0 means the available parallelism), this function should be called before reading.   
+    /// the decompressed xml is split into chunks at row boundaries, the chunks are parsed in parallel and the rows are filtered in order (e.g. with_skip_until).   
+    /// limits in parallel mode:   
+    /// - it can not be used with with_fill_merged (InvalidState is returned), and get_merged_ranges will scan the sheet again   
+    /// - the progress callback is called after each wave of chunks (about 4MB of xml per thread) instead of every n rows   
+    /// - the cancel token is checked between rows within each chunk, the rows of the current wave are discarded when cancelled
+    pub fn with_parallel(&mut self, threads: usize) -> Result<()> {
+        if self.currow > 0 || self.status != 1 {
+            return Err(XlsxError::InvalidState("with_parallel should be called before reading".to_string()));
+        }
+        if self.fill_merged {
+            return Err(XlsxError::InvalidState("with_parallel can not be used with with_fill_merged".to_string()));
+        }
+        self.parallel = if threads == 0 {
+            std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+        } else {
+            threads
+        };
+        Ok(())
+    }
+    /// fill the value of the top left cell into every cell covered by the merged range, this function should be called before reading.   
+    /// the merged ranges will be scanned ahead of sheet data, at the cost of one extra decompression pass.   
+    /// call it before into_cached_sheet if the cached sheet should fill merged ranges whose top left cell is outside the cached range
+    pub fn with_fill_merged(&mut self, fill: bool) -> Result<()> {
+        if fill && self.currow > 0 {
+            return Err(XlsxError::InvalidState("with_fill_merged should be called before reading".to_string()));
+        }
+        if fill && self.parallel > 0 {
+            return Err(XlsxError::InvalidState("with_parallel can not be used with with_fill_merged".to_string()));
+        }
+        if fill && self.merged_rects.is_none() {
+            self.with_prescan()?;
+        }
+        self.merged_tops = match (fill, &self.merged_rects) {
+            (true, Some(mgs)) => mgs.iter().map(|(left_top, _)| (*left_top, None)).collect(),
+            _ => HashMap::new()
+        };
+        self.fill_merged = fill;
+        Ok(())
+    }
     /// capture values by address
     pub fn with_capture_vals(&mut self, captures: HashSet<String>) {
         if captures.len() > 0 {
@@ -590,7 +2114,7 @@ impl<'a> XlsxSheet<'a> {
             self.get_header_row()?;
             Ok(&self.vals_captures)
         } else {
-            Err(anyhow!("get_captured_vals error: first_row_is_header must be true"))
+            Err(XlsxError::InvalidState("get_captured_vals error: first_row_is_header must be true".to_string()))
         }
     }
     /// check whether the sheet is empty, should be called after at least one row has been read
@@ -600,7 +2124,7 @@ impl<'a> XlsxSheet<'a> {
         } else if self.status == 0 {
             Ok(true)
         } else {
-            Err(anyhow!("is_empty should be called after at least one row has been read"))
+            Err(XlsxError::InvalidState("is_empty should be called after at least one row has been read".to_string()))
         }
     }
     /// get column range, v0.1.7 the start column number included (start from 1)
@@ -609,64 +2133,68 @@ impl<'a> XlsxSheet<'a> {
     }
     /// get next row
     fn get_next_row(&mut self) -> Result<Option<(u32, Vec<CellValue<'a>>)>> {
+        if let Some(v) = self.pending_rows.pop_front() {
+            return Ok(Some(v))
+        }  //  优先返回自动识别标题行时已读取的数据行
+        let mut row_value = Vec::new();
+        Ok(self.read_row_into(&mut row_value)?.map(|r| (r, row_value)))
+    }
+    /// read next row into the buffer (pending rows excluded), returns the row number
+    fn read_row_into(&mut self, row_value: &mut Vec<CellValue<'a>>) -> Result<Option<u32>> {
+        if self.parallel > 0 {
+            return self.read_row_parallel(row_value);
+        }
+        if self.status == 0 {
+            return Ok(None)
+        }  //  已关闭的sheet直接返回None
+        let mut sc = std::mem::take(&mut self.scratch);
+        sc.prev_head.clear();
+        let res = self.parse_row(row_value, &mut sc);
+        self.scratch = sc;
+        res
+    }
+    /// parse xml events until a row is read
+    fn parse_row(&mut self, row_value: &mut Vec<CellValue<'a>>, sc: &mut RowScratch) -> Result<Option<u32>> {
         let mut col: ColNum = 0;
-        let mut cell_addr = "".into();
-        let mut cell_type = vec![];
-        let mut prev_head = vec![];
         let mut col_index: ColNum = 1;    // 当前需增加cell的col_index
         // let mut row_num: u32 = 0;     //  sheet中增加currow储存当前行号
-        let mut row_value: Vec<CellValue<'_>> = Vec::new();
         let mut num_fmt_id: u32 = 0;
-        if self.status == 0 {
-            return Ok(None)
-        }  //  已关闭的sheet直接返回None
         loop {
             match self.reader.read_event_into(&mut self.buf) {
                 Ok(Event::Start(ref e)) => {
-                    prev_head = e.name().as_ref().to_vec();
+                    sc.prev_head.clear();
+                    sc.prev_head.extend_from_slice(e.local_name().as_ref());
                     if self.status == 0 {
                         break Ok(None)
                     } else if self.status == 1 {
-                        if prev_head == b"dimension" {
+                        if sc.prev_head == b"dimension" {
                             let attr = get_attr_val!(e, "ref", to_string);
                             let dim: Vec<&str> = attr.split(':').collect();
                             if let Some(x) = dim.get(1) {
                                 self.max_size = Some(get_tuple_from_ord(x.as_bytes())?);
                             };
-                        } else if prev_head == b"sheetData" {
+                        } else if sc.prev_head == b"sheetData" {
                             self.status = 2;
-                        } else if prev_head == b"mergeCells" {
+                        } else if sc.prev_head == b"mergeCells" {
                             let cnt: usize = get_attr_val!(e, "count", parse);
                             self.process_merged_cells(cnt)?;
                         }; 
                     } else {
-                        if prev_head == b"c" {
-                            match e.try_get_attribute("t")? {
-                                Some(attr) => {
-                                    cell_type = attr.unescape_value()?.as_bytes().to_owned();
-                                },
-                                _ => {
-                                    cell_type = b"n".to_vec();
-                                }
-                            };
-                            match e.try_get_attribute("s")? {
-                                Some(attr) => {
-                                    num_fmt_id = self.map_style[&attr.unescape_value()?.parse::<u32>()?];
-                                },
-                                _ => {
-                                    num_fmt_id = 0;
-                                }
-                            };
-                            cell_addr = get_attr_val!(e, "r").to_string();   //  单元格地址
-                            col = get_num_from_ord(cell_addr.as_bytes()).unwrap_or(0);
-                            
+                        if sc.prev_head == b"c" {
+                            let cells = CellCtx { key: &self.key, str_share: self.str_share, map_style: self.map_style, datetime_fmts: self.datetime_fmts, options: &self.options };
+                            (col, num_fmt_id) = sc.start_cell(e, &cells, self.currow, &mut self.warnings)?;
                             if self.currow > self.skip_rows && col > self.left_ncol && col <= self.right_ncol {
                                 self.status = 3;   // 3-get_cell; 4-skip_cell;
+                            } else if self.merged_tops.contains_key(&(self.currow, col)) {
+                                self.status = 5;   // 5-get_merged_cell, 仅获取区域外合并单元格的值
                             } else {
                                 self.status = 4;   // 3-get_cell; 4-skip_cell;
                             }
-                        } else if prev_head == b"row" {
+                        } else if sc.prev_head == b"row" {
                             self.currow = get_attr_val!(e, "r", parse);
+                            if let Some(n) = self.options.limits.max_rows.filter(|n| self.currow > *n) {
+                                return Err(XlsxError::LimitExceeded(format!("{}: row {} exceeds the limit of {}", self.key, self.currow, n)));
+                            }
                             let cap = {
                                 if self.right_ncol == MAX_COL_NUM {
                                     match e.try_get_attribute("spans") {
@@ -689,75 +2217,49 @@ impl<'a> XlsxSheet<'a> {
                                 } else {
                                     self.right_ncol
                                 }
-                            } - self.left_ncol;
-                            row_value = Vec::with_capacity(cap.into());
+                            }.saturating_sub(self.left_ncol);
+                            row_value.clear();
+                            row_value.reserve(cap.into());
                             col_index = 1;         // 当前需增加cell的col_index
                             // row_value.push(CellValue::Number(row_num as f64));  // 行号单独返回
                         }; 
                     };
                 },
                 Ok(Event::Empty(ref e)) => {
-                    prev_head = e.name().as_ref().to_vec();
-                    if self.status == 1 && prev_head == b"dimension" {
+                    sc.prev_head.clear();
+                    sc.prev_head.extend_from_slice(e.local_name().as_ref());
+                    if self.status == 1 && sc.prev_head == b"dimension" {
                         let attr = get_attr_val!(e, "ref", to_string);
                         let dim: Vec<&str> = attr.split(':').collect();
                         if let Some(x) = dim.get(1) {
                             self.max_size = Some(get_tuple_from_ord(x.as_bytes())?);
                         };
-                    } else if prev_head == b"sheetData" {
+                    } else if sc.prev_head == b"sheetData" {
                         self.status = 0;
+                        self.report_progress(true);
                         break Ok(None)
                     }
                 },
-                Ok(Event::Text(ref t)) => {
-                    // b for boolean
-                    // d for date
-                    // e for error
-                    // inlineStr for an inline string (i.e., not stored in the shared strings part, but directly in the cell)
-                    // n for number
-                    // s for shared string (so stored in the shared strings part and not in the cell)
-                    // str for a formula (a string representing the formula)
-                    if self.status == 3 && (prev_head == b"v" || prev_head == b"t") {
+                // b for boolean
+                // d for date
+                // e for error
+                // inlineStr for an inline string (i.e., not stored in the shared strings part, but directly in the cell)
+                // n for number
+                // s for shared string (so stored in the shared strings part and not in the cell)
+                // str for a formula (a string representing the formula)
+                Ok(Event::Text(ref t)) if (self.status == 3 || self.status == 5) && (sc.prev_head == b"v" || sc.prev_head == b"t") => {
+                    let cells = CellCtx { key: &self.key, str_share: self.str_share, map_style: self.map_style, datetime_fmts: self.datetime_fmts, options: &self.options };
+                    let cel_val = sc.cell_value(t, &cells, num_fmt_id, self.currow, col, &mut self.warnings)?;
+                    if let Some(top) = self.merged_tops.get_mut(&(self.currow, col)) {
+                        *top = Some(cel_val.clone());
+                    }
+                    if self.status == 3 {
                         while col_index + self.left_ncol < col {
                             row_value.push(CellValue::Blank);
                             col_index += 1;
                         }
-                        let cel_val = if cell_type == b"inlineStr" && prev_head == b"t" { 
-                            CellValue::String(String::from_utf8(t.to_vec())?)
-                        } else if prev_head == b"v" {
-                            if cell_type == b"s" {
-                                CellValue::Shared(&self.str_share[String::from_utf8(t.to_vec())?.parse::<usize>()?])
-                            } else if cell_type == b"n" {
-                                let fmt = self.datetime_fmts.get(&num_fmt_id).unwrap_or(&FMT_DEFAULT);
-                                if *fmt == FMT_DATE {
-                                    CellValue::Date(String::from_utf8(t.to_vec())?.parse::<f64>()?)
-                                } else if *fmt == FMT_DATETIME {
-                                    CellValue::Datetime(String::from_utf8(t.to_vec())?.parse::<f64>()?)
-                                } else if *fmt == FMT_TIME {
-                                    CellValue::Time(String::from_utf8(t.to_vec())?.parse::<f64>()?)
-                                } else {
-                                    CellValue::Number(String::from_utf8(t.to_vec())?.parse::<f64>()?)
-                                }
-                            } else if cell_type == b"b" {
-                                if String::from_utf8(t.to_vec())?.parse::<usize>() == Ok(1) {
-                                    CellValue::Bool(true)
-                                } else {
-                                    CellValue::Bool(false)
-                                }
-                            } else if cell_type == b"d" {
-                                CellValue::String(String::from_utf8(t.to_vec())?)
-                            } else if cell_type == b"e" {
-                                CellValue::Error(String::from_utf8(t.to_vec())?)
-                            } else if cell_type == b"str" {
-                                CellValue::String(String::from_utf8(t.to_vec())?)
-                            } else{
-                                CellValue::Blank
-                            }
-                        } else {
-                            CellValue::Error("Unknown cell type".into())
-                        };
                         if let Some(addrs) = &mut self.addr_captures {
-                            if let Some(key) = addrs.take(&cell_addr) {
+                            if let Some(key) = addrs.take(&sc.cell_addr) {
                                 self.vals_captures.insert(key, cel_val.clone());
                             }
                         }
@@ -767,139 +2269,299 @@ impl<'a> XlsxSheet<'a> {
                 },
                 Ok(Event::End(ref e)) => {
                     // 0-closed; 1-new; 2-active;
-                    if (e.name().as_ref() == b"row") && self.status > 1 && row_value.len() > 0 {
-                        if let Some(skip_until) = &self.skip_until {
-                            if is_matched_row(&row_value, skip_until, true).0 {
-                                self.skip_until = None;
-                            } else {
-                                // col = 0;   //  reset each cell
-                                // cell_type = Vec::new();   // reset each cell
-                                // num_fmt_id = 0;   // reset each cell
-                                // prev_head = Vec::new();    reset each tag
-                                // col_index = 1;    // 当前需增加cell的col_index  // reset each row
-                                // row_num = 0;       //  reset each row
-                                // row_value = Vec::new();    // reset each row
-                                continue;
-                            }   //  读取到初始行前继续读取
-                        } else if let Some(read_before) = &self.read_before {
-                            if is_matched_row(&row_value, read_before, true).0 {
-                                self.status = 0; 
-                                self.read_before = None;
-                                break Ok(None);
-                            }  //  读取到结尾行后不再继续读取，且抛弃结尾行
-                        };
-                        if self.right_ncol != MAX_COL_NUM {
-                            while row_value.len() < row_value.capacity() {
-                                row_value.push(CellValue::Blank);
-                            };
+                    let (is_row, is_sheet_data) = (e.local_name().as_ref() == b"row", e.local_name().as_ref() == b"sheetData");
+                    if is_row && self.status > 1 {
+                        self.rows_processed += 1;
+                        self.report_progress(false);
+                        if self.cancel_token.as_ref().map(|t| t.is_cancelled()).unwrap_or(false) {
+                            self.status = 0;
+                            return Err(XlsxError::Cancelled);
                         }
-                        
-                        // 处理标题行
-                        if !self.first_row_is_header {    //  不跳过标题行
-                            if let Some(skip_matched) = &self.skip_matched {
-                                if is_matched_row(&row_value, skip_matched, self.skip_matched_check_by_and).0 {
-                                    continue;    //   如果当前行满足条件，忽略当前行; 
-                                }
-                            } 
-                        };
-                        self.addr_captures = None;    //  返回首行后，不再匹配captures
-                        break Ok(Some((self.currow, row_value)))
-                    }else if e.name().as_ref() == b"sheetData" {
+                    }
+                    if self.fill_merged && is_row && self.status > 1 && self.currow > self.skip_rows {
+                        self.fill_merged_cells(row_value);
+                    }
+                    if is_row && self.status > 1 && !row_value.is_empty() {
+                        match self.filter_row(row_value) {
+                            RowAction::Skip => continue,    //  读取到初始行前继续读取，或忽略满足条件的行
+                            RowAction::Stop => break Ok(None),
+                            RowAction::Accept => break Ok(Some(self.currow)),
+                        }
+                    }else if is_sheet_data {
                         self.status = 0; 
+                        self.report_progress(true);
                         break Ok(None)
                     }
                 },
                 Ok(Event::Eof) => {
-                    self.status = 0; 
+                    if self.status > 0 {
+                        self.status = 0; 
+                        self.report_progress(true);
+                    }
                     break Ok(None)
                 },   // exits the loop when reaching end of file
                 Err(e) => {
-                    return Err(anyhow!("sheet data is broken: {:?}", e));
+                    let err = XlsxError::from_xml("sheet data", e);
+                    if self.options.strict || matches!(err, XlsxError::LimitExceeded(_)) {
+                        return Err(err);
+                    }
+                    //  宽松模式下，xml损坏时结束读取
+                    self.warnings.push(format!("row {}: {}", self.currow, err));
+                    self.status = 0;
+                    break Ok(None)
                 },
                 _ => ()                  // There are several other `Event`s we do not consider here
             }
             self.buf.clear();
         }
     }
-    /// get header if first_row_is_header is true
-    pub fn get_header_row(&mut self) -> Result<(u32, Vec<CellValue<'a>>)> {
-        if self.first_row_is_header {
-            match self.get_next_row() {
-                Ok(Some(v)) => {
-                    if let Some(header_check) = &self.header_check {
-                        let matched = is_matched_row(&v.1, header_check, true);
-                        if matched.0 {
-                            self.first_row = Some(v);
-                            self.first_row_is_header = false;
-                        } else {
-                            return Err(anyhow!("header row check failed: {}", matched.1));
+    /// read next row from the rows parsed in parallel, the filters are evaluated in order
+    fn read_row_parallel(&mut self, row_value: &mut Vec<CellValue<'a>>) -> Result<Option<u32>> {
+        loop {
+            let (r, mut row) = match self.parsed_rows.pop_front() {
+                Some(v) => v,
+                None if self.status == 0 => return Ok(None),
+                None => {
+                    self.parse_wave()?;
+                    continue;
+                }
+            };
+            self.currow = r;
+            if let Some(addrs) = &mut self.addr_captures {
+                for (j, cell) in row.iter().enumerate() {
+                    if !matches!(cell, CellValue::Blank) {
+                        if let Some(key) = addrs.take(&get_ord_from_tuple(r, self.left_ncol + j as ColNum + 1)?) {
+                            self.vals_captures.insert(key, cell.clone());
                         }
-                    } else {
-                        self.first_row = Some(v);
-                        self.first_row_is_header = false;
                     }
+                }
+            }
+            match self.filter_row(&mut row) {
+                RowAction::Skip => continue,
+                RowAction::Stop => {
+                    self.parsed_rows.clear();
+                    return Ok(None);
                 },
-                Ok(None) => {},
-                Err(e) => {return Err(e)}
+                RowAction::Accept => {
+                    *row_value = row;
+                    return Ok(Some(r));
+                }
+            }
+        }
+    }
+    /// decompress the next part of the sheet data, split it at row boundaries and parse the chunks on threads
+    fn parse_wave(&mut self) -> Result<()> {
+        if self.status == 1 {
+            //  读取至sheetData开始
+            loop {
+                match self.reader.read_event_into(&mut self.buf) {
+                    Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"dimension" => {
+                        let attr = get_attr_val!(e, "ref", to_string);
+                        if let Some(x) = attr.split(':').nth(1) {
+                            self.max_size = Some(get_tuple_from_ord(x.as_bytes())?);
+                        };
+                    },
+                    Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"sheetData" => {
+                        self.status = 2;
+                        break;
+                    },
+                    Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"sheetData" => {
+                        self.status = 0;
+                        break;
+                    },
+                    Ok(Event::Eof) => {
+                        self.status = 0;
+                        break;
+                    },
+                    Err(e) => return Err(XlsxError::from_xml("sheet data", e)),
+                    _ => ()
+                }
+                self.buf.clear();
+            }
+            self.buf.clear();
+            if self.status == 0 {
+                self.report_progress(true);
+                return Ok(());
+            }
+        }
+        if self.cancel_token.as_ref().map(|t| t.is_cancelled()).unwrap_or(false) {
+            self.status = 0;
+            return Err(XlsxError::Cancelled);
+        }
+        //  读取若干完整行
+        let mut wave = std::mem::take(&mut self.raw_tail);
+        let mut eof = false;
+        let cut = loop {
+            let want = (self.chunk_size * self.parallel) as u64;
+            let n = self.reader.get_mut().take(want).read_to_end(&mut wave)?;
+            if (n as u64) < want {
+                eof = true;
+                break wave.len();
+            }
+            match (0..wave.len()).rev().find(|i| wave[*i] == b'<' && is_row_start(&wave[*i..])) {
+                Some(i) if i > 0 => break i,
+                _ => ()
+            }
+        };
+        self.raw_tail = wave.split_off(cut);
+        //  按行边界切分
+        let mut bounds = vec![0];
+        let step = max(wave.len() / self.parallel, 1);
+        while let Some(next) = find_row_start(&wave, bounds[bounds.len()-1] + step) {
+            bounds.push(next);
+        }
+        bounds.push(wave.len());
+        let ctx = ChunkCtx {
+            cells: CellCtx { key: &self.key, str_share: self.str_share, map_style: self.map_style, datetime_fmts: self.datetime_fmts, options: &self.options },
+            skip_rows: self.skip_rows,
+            left_ncol: self.left_ncol,
+            right_ncol: self.right_ncol,
+            cancel_token: self.cancel_token.as_ref(),
+        };
+        let results: Vec<Result<ChunkRows<'a>>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = bounds.windows(2).map(|w| {
+                let (chunk, ctx) = (&wave[w[0]..w[1]], &ctx);
+                scope.spawn(move || parse_chunk(chunk, ctx))
+            }).collect();
+            handles.into_iter().map(|h| h.join().unwrap_or_else(|_| Err(XlsxError::InvalidState("parsing thread panicked".to_string())))).collect()
+        });
+        for res in results {
+            let chunk = match res {
+                Ok(v) => v,
+                Err(e) => {
+                    self.status = 0;
+                    return Err(e);
+                }
+            };
+            self.rows_processed += chunk.processed;
+            self.warnings.extend(chunk.warnings);
+            self.parsed_rows.extend(chunk.rows);
+            if chunk.end {
+                eof = true;
+                break;
+            }
+        }
+        if eof {
+            self.status = 0;
+            self.raw_tail = Vec::new();
+        }
+        self.report_progress(true);
+        Ok(())
+    }
+    /// apply skip_until, read_before and skip_matched to the current row in order
+    fn filter_row(&mut self, row_value: &mut Vec<CellValue<'a>>) -> RowAction {
+        if self.read_before_matched {
+            self.status = 0;    //  检查点之前已读取到结尾行
+            return RowAction::Stop;
+        }
+        if let Some(skip_until) = &self.skip_until {
+            if is_matched_row(row_value, skip_until, true).0 {
+                self.skip_until = None;
+                self.skip_until_matched = true;
+            } else {
+                return RowAction::Skip;
+            }   //  读取到初始行前继续读取
+        } else if let Some(read_before) = &self.read_before {
+            if is_matched_row(row_value, read_before, true).0 {
+                self.status = 0; 
+                self.read_before = None;
+                self.read_before_matched = true;
+                return RowAction::Stop;
+            }  //  读取到结尾行后不再继续读取，且抛弃结尾行
+        };
+        if self.right_ncol != MAX_COL_NUM {
+            let ncol = (self.right_ncol - self.left_ncol) as usize;
+            while row_value.len() < ncol {
+                row_value.push(CellValue::Blank);
+            };
+        }
+        
+        // 处理标题行
+        if !self.first_row_is_header {    //  不跳过标题行
+            if let Some(skip_matched) = &self.skip_matched {
+                if is_matched_row(row_value, skip_matched, self.skip_matched_check_by_and).0 {
+                    return RowAction::Skip;    //   如果当前行满足条件，忽略当前行; 
+                }
+            } 
+        };
+        if !self.first_row_is_header || self.header_rows.len() + 1 >= self.header_nrows {
+            self.addr_captures = None;    //  返回标题行后，不再匹配captures
+        }
+        RowAction::Accept
+    }
+    /// fill the merged cells of current row
+    fn fill_merged_cells(&self, row_value: &mut Vec<CellValue<'a>>) {
+        if let Some(mgs) = &self.merged_rects {
+            for (left_top, right_end) in mgs.ranges_in_row(self.currow) {
+                if let Some(Some(val)) = self.merged_tops.get(left_top) {
+                    for c in max(left_top.1, self.left_ncol+1)..=std::cmp::min(right_end.1, self.right_ncol) {
+                        if (self.currow, c) == *left_top {
+                            continue;
+                        }
+                        let inx = (c - self.left_ncol - 1) as usize;
+                        while row_value.len() <= inx {
+                            row_value.push(CellValue::Blank);
+                        }
+                        row_value[inx] = val.clone();
+                    }
+                }
+            }
+        }
+    }
+    /// get header if first_row_is_header is true, the last row will be returned if with_header_rows(n) is set
+    pub fn get_header_row(&mut self) -> Result<(u32, Vec<CellValue<'a>>)> {
+        if self.first_row_is_header {
+            while self.header_rows.len() < self.header_nrows {
+                match self.get_next_row() {
+                    Ok(Some(v)) => {self.header_rows.push(v);},
+                    Ok(None) => {break;},
+                    Err(e) => {return Err(e)}
+                }
+            }
+            if let Some(v) = self.header_rows.last() {
+                if let Some(header_check) = &self.header_check {
+                    let matched = is_matched_row(&v.1, header_check, true);
+                    if !matched.0 {
+                        return Err(XlsxError::HeaderCheckFailed { sheet: self.key.clone(), row: v.0, expected: matched.1 });
+                    }
+                }
+                self.first_row = Some(v.clone());
+                self.header_names = Some(flatten_header_rows(&self.header_rows, self.left_ncol, self.merged_rects.as_ref()));
+                self.first_row_is_header = false;
             }
         }
         match &self.first_row {
             Some(v) => Ok(v.clone()),
-            None => Err(anyhow!("no header row！"))
+            None => Err(XlsxError::NoHeaderRow(self.key.clone()))
+        }
+    }
+    /// get column names if first_row_is_header is true, multi header rows are flattened into names like `Q1.Revenue`
+    pub fn get_header_names(&mut self) -> Result<Vec<String>> {
+        self.get_header_row()?;
+        match &self.header_names {
+            Some(v) => Ok(v.clone()),
+            None => Err(XlsxError::NoHeaderRow(self.key.clone()))
         }
     }
+    /// get column number (start from 1) by column name, which can be converted by get_ord_from_num for with_* functions
+    pub fn get_column_by_name<S: AsRef<str>>(&mut self, name: S) -> Result<Option<ColNum>> {
+        let names = self.get_header_names()?;
+        Ok(names.iter().position(|v| v == name.as_ref()).map(|i| self.left_ncol + i as ColNum + 1))
+    }
     fn process_merged_cells(&mut self, count: usize) -> Result<()> {
         if self.status == 1 || self.status == 0 {
-            if self.merged_rects.is_none() {
-                self.merged_rects = Some(vec![]);
-            }
+            let mut rects = Vec::with_capacity(count);
             loop {
                 match self.reader.read_event_into(&mut self.buf) {
-                    Ok(Event::Start(ref e)) => {
-                        if e.name().as_ref() == b"mergeCell" {
-                            let attr = get_attr_val!(e, "ref", to_string);
-                            let dim: Vec<&str> = attr.split(':').collect();
-                            if let Some(x) = dim.get(0) {
-                                let left_top = get_tuple_from_ord(x.as_bytes())?;
-                                let right_end =  if let Some(x) = dim.get(1) {
-                                    get_tuple_from_ord(x.as_bytes())?
-                                } else {
-                                    return Err(anyhow!("mergeCell error：{}", attr));
-                                };
-                                if let Some(ref mut mgs) = self.merged_rects {
-                                    mgs.push((left_top, right_end))
-                                };
-                            } else {
-                                return Err(anyhow!("mergeCell error：{}", attr));
-                            }; 
-                        }
-                    },
-                    Ok(Event::Empty(ref e)) => {
-                        if e.name().as_ref() == b"mergeCell" {
-                            let attr = get_attr_val!(e, "ref", to_string);
-                            let dim: Vec<&str> = attr.split(':').collect();
-                            if let Some(x) = dim.get(0) {
-                                let left_top = get_tuple_from_ord(x.as_bytes())?;
-                                let right_end =  if let Some(x) = dim.get(1) {
-                                    get_tuple_from_ord(x.as_bytes())?
-                                } else {
-                                    return Err(anyhow!("mergeCell error：{}", attr));
-                                };
-                                if let Some(ref mut mgs) = self.merged_rects {
-                                    mgs.push((left_top, right_end))
-                                };
-                            } else {
-                                return Err(anyhow!("mergeCell error：{}", attr));
-                            }; 
-                        }
+                    Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"mergeCell" => {
+                        match get_merged_range(&get_attr_val!(e, "ref", to_string)) {
+                            Ok(rng) => rects.push(rng),
+                            Err(e) if self.options.strict => return Err(e),
+                            Err(e) => self.warnings.push(e.to_string())
+                        };
                     },
-                    Ok(Event::End(ref e)) => {
-                        if e.name().as_ref() != b"mergeCells" {
-                            break;
-                        }
-                        else if e.name().as_ref() != b"mergeCell" {
-                            break;
-                        }
+                    Ok(Event::End(ref e)) if e.local_name().as_ref() == b"mergeCells" => {
+                        break;
                     },
                     Ok(Event::Eof) => {
                         break;
@@ -907,40 +2569,68 @@ impl<'a> XlsxSheet<'a> {
                     _ => {}
                 }
             };
-            if let Some(ref rects) = self.merged_rects {
-                if rects.len() != count {
-                    self.merged_rects = None;
-                    return Err(anyhow!("the number of merged ranges is not equal to the number of rows"));
-                };
-            }
+            if rects.len() != count {
+                if self.options.strict {
+                    return Err(XlsxError::MalformedXml("the number of merged ranges is not equal to the count of mergeCells".to_string()));
+                }
+                self.warnings.push(format!("mergeCells: count is {}, but {} merged ranges found", count, rects.len()));
+            };
+            self.merged_rects = Some(MergedRanges::new(rects));
         }
         Ok(())
     }
-    /// get merged ranges, call after all data getched
-    pub fn get_merged_ranges(&mut self) -> Result<&Vec<MergedRange>> {
+    /// get merged ranges, call after all data getched or with_prescan called
+    pub fn get_merged_ranges(&mut self) -> Result<&MergedRanges> {
+        if self.merged_rects.is_none() && self.parallel > 0 && self.status == 0 {
+            self.with_prescan()?;    //  并行解析时已读取全部字节，需重新扫描
+        }
         if self.merged_rects.is_none() {
             if self.status == 0 {  // 已关闭的情况下读取合并单元格
                 loop {
                     match self.reader.read_event_into(&mut self.buf) {
-                        Ok(Event::Start(ref e)) => {
-                            if e.name().as_ref() == b"mergeCells" {
-                                let cnt: usize = get_attr_val!(e, "count", parse);
-                                self.process_merged_cells(cnt)?;
-                                break;
-                            };
+                        Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"mergeCells" => {
+                            let cnt: usize = get_attr_val!(e, "count", parse);
+                            self.process_merged_cells(cnt)?;
+                            break;
+                        },
+                        Ok(Event::Eof) => {
+                            self.merged_rects = Some(MergedRanges::default());
+                            break;
+                        },
+                        Err(e) => {
+                            return Err(XlsxError::from_xml("sheet data", e));
                         },
                         _ => {}
                     }
                 };
             } else {
-                return Err(anyhow!("finish fetching data first"));
+                return Err(XlsxError::InvalidState("finish fetching data first".to_string()));
             }
         };
         if let Some(ref rects) = self.merged_rects {
             Ok(rects)
         } else {
-            return Err(anyhow!("merged_rects error"));
+            return Err(XlsxError::InvalidState("merged_rects error".to_string()));
+        }
+    }
+    /// visit the remaining rows one by one, the row buffer is reused instead of allocating a new row for each.   
+    /// the header row is not visited if first_row_is_header is true, an error returned by the visitor stops reading
+    pub fn for_each_row<F: FnMut(RowNum, &[CellValue<'a>]) -> Result<()>>(&mut self, mut visitor: F) -> Result<()> {
+        if self.first_row_is_header {
+            self.get_header_row()?;
+        }
+        while let Some((r, row)) = self.pending_rows.pop_front() {
+            self.validate_row(r, &row);
+            visitor(r, &row)?;
+            self.returned_row = r;
         }
+        let mut row_value = Vec::new();
+        while let Some(r) = self.read_row_into(&mut row_value)? {
+            self.validate_row(r, &row_value);
+            visitor(r, &row_value)?;
+            self.returned_row = r;
+        }
+        Ok(())
     }
     /// Get all the remaining data
     pub fn get_remaining_cells(&mut self) -> Result<Option<(Vec<u32>, Vec<Vec<CellValue<'_>>>)>> {
@@ -950,19 +2640,23 @@ impl<'a> XlsxSheet<'a> {
         match self.get_next_row() {
             Ok(Some((r, d))) => {
                 let (mut rows, mut data) = if let Some((rn, _)) = self.max_size {
-                    (Vec::with_capacity(max(1, rn-r+1) as usize), Vec::with_capacity(rn as usize))
+                    let cap = std::cmp::min(max(1, rn.saturating_sub(r)+1) as usize, MAX_RESERVED);
+                    (Vec::with_capacity(cap), Vec::with_capacity(cap))
                 } else {
                     (Vec::new(), Vec::new())
                 };
+                self.validate_row(r, &d);
                 rows.push(r);
                 data.push(d);
                 loop {
                     match self.get_next_row() {
                         Ok(Some((r, d))) => {
+                            self.validate_row(r, &d);
                             rows.push(r);
                             data.push(d);
                         },
                         Ok(None) => {
+                            self.returned_row = rows[rows.len()-1];
                             break Ok(Some((rows, data)));
                         },
                         Err(e) => {
@@ -995,14 +2689,17 @@ impl<'a> Iterator for XlsxSheet<'a> {
         loop {
             match self.get_next_row() {
                 Ok(Some(v)) => {
+                    self.validate_row(v.0, &v.1);
                     nums.push(v.0);
                     data.push(v.1);
                     if nums.len() >= self.iter_batch { 
+                        self.returned_row = v.0;
                         break Some(Ok((nums, data)))
                     }
                 },
                 Ok(None) => {
-                    if nums.len() > 0 {
+                    if let Some(r) = nums.last() {
+                        self.returned_row = *r;
                         break Some(Ok((nums, data)))
                     } else {
                         break None
@@ -1031,7 +2728,13 @@ pub struct CachedSheet<'a> {
     left_ncol: ColNum,
     right_ncol: ColNum,
     header_row: Option<(u32, Vec<CellValue<'a>>)>,
-    merged_rects: Vec<((RowNum, ColNum), (RowNum, ColNum))>
+    header_names: Option<Vec<String>>,
+    warnings: Vec<String>,
+    sheet_tail: Option<SheetTail>,
+    violations: Vec<ValidationViolation>,
+    merged_rects: MergedRanges,
+    merged_tops: HashMap<(RowNum, ColNum), CellValue<'a>>,    //  缓存范围之外的合并区域左上角的值
+    tops_captured: bool,    //  是否已通过XlsxSheet::with_fill_merged记录缓存范围之外的左上角的值
 }
 
 #[cfg(feature = "cached")]
@@ -1041,10 +2744,49 @@ impl <'a> CachedSheet<'a> {
         self.keep_empty = keep_empty;
         self
     }
+    /// fill the value of the top left cell into every cell covered by the merged range.   
+    /// if the top left cell of a merged range is outside the cached range (e.g. skip_rows or left_ncol is set), XlsxSheet::with_fill_merged(true) should be called before into_cached_sheet
+    /// to read it ahead, otherwise InvalidState is returned
+    pub fn with_fill_merged(mut self, fill: bool) -> Result<Self> {
+        if fill {
+            for (left_top, right_end) in self.merged_rects.iter() {
+                if right_end.0 < self.top_nrow || right_end.1 < self.left_ncol {
+                    continue;
+                }
+                let inside = left_top.0 >= self.top_nrow && left_top.1 >= self.left_ncol;
+                if !inside && !self.tops_captured {
+                    return Err(XlsxError::InvalidState(format!("the top left cell of merged range {} is outside the cached range, call XlsxSheet::with_fill_merged before into_cached_sheet", get_ord_from_tuple(left_top.0, left_top.1)?)));
+                }
+                let val = match inside {
+                    true => self.data.get(&left_top.0).and_then(|r| r.get((left_top.1 - self.left_ncol) as usize)),
+                    false => self.merged_tops.get(left_top)
+                };
+                let val = match val {
+                    Some(v) => v.clone(),
+                    None => continue
+                };
+                for r in max(left_top.0, self.top_nrow)..=std::cmp::min(right_end.0, self.bottom_nrow) {
+                    let row_value = self.data.entry(r).or_default();
+                    for c in max(left_top.1, self.left_ncol)..=std::cmp::min(right_end.1, self.right_ncol) {
+                        let inx = (c - self.left_ncol) as usize;
+                        while row_value.len() <= inx {
+                            row_value.push(CellValue::Blank);
+                        }
+                        row_value[inx] = val.clone();
+                    }
+                }
+            }
+        }
+        Ok(self)
+    }
     /// get sheet name
     pub fn sheet_name(&self) -> &String {
         &self.key
     }
+    /// get warnings recorded in lenient mode (ReadOptions.strict is false)
+    pub fn get_warnings(&self) -> &Vec<String> {
+        &self.warnings
+    }
     /// check whether the sheet is empty
     pub fn is_empty(&self) -> bool {
         self.empty
@@ -1061,13 +2803,63 @@ impl <'a> CachedSheet<'a> {
     pub fn get_header_row(&self) -> Result<(u32, Vec<CellValue<'a>>)> {
         match &self.header_row {
             Some(v) => Ok(v.clone()),
-            None => Err(anyhow!("no header row！"))
+            None => Err(XlsxError::NoHeaderRow(self.key.clone()))
         }
     }
+    /// get column names if first_row_is_header is true, multi header rows are flattened into names like `Q1.Revenue`
+    pub fn get_header_names(&self) -> Result<&Vec<String>> {
+        match &self.header_names {
+            Some(v) => Ok(v),
+            None => Err(XlsxError::NoHeaderRow(self.key.clone()))
+        }
+    }
+    /// get column number (start from 1) by column name
+    pub fn get_column_by_name<S: AsRef<str>>(&self, name: S) -> Result<Option<ColNum>> {
+        Ok(self.get_header_names()?.iter().position(|v| v == name.as_ref()).map(|i| self.left_ncol + i as ColNum))
+    }
     /// get merged ranges, call as any time
-    pub fn get_merged_ranges(&self) -> &Vec<MergedRange> {
+    pub fn get_merged_ranges(&self) -> &MergedRanges {
         &self.merged_rects
     }
+    /// get data validation rules, None if with_prescan (or with_validation) is not called before into_cached_sheet.   
+    /// the same applies to the other trailing elements below
+    pub fn get_data_validations(&self) -> Option<&Vec<DataValidation>> {
+        self.sheet_tail.as_ref().map(|t| &t.data_validations)
+    }
+    /// get violations of data validation rules, with_validation must be called before into_cached_sheet
+    pub fn get_violations(&self) -> &Vec<ValidationViolation> {
+        &self.violations
+    }
+    /// get layout of the sheet, None if the sheet is not prescanned. row heights are included only if XlsxSheet::get_layout is called before into_cached_sheet
+    pub fn get_layout(&self) -> Option<&SheetLayout> {
+        self.sheet_tail.as_ref().map(|t| &t.layout)
+    }
+    /// get conditional formatting, None if the sheet is not prescanned
+    pub fn get_conditional_formats(&self) -> Option<&Vec<ConditionalFormat>> {
+        self.sheet_tail.as_ref().map(|t| &t.conditional_formats)
+    }
+    /// get protection of the sheet, None if not set or not prescanned
+    pub fn get_protection(&self) -> Option<&SheetProtection> {
+        self.sheet_tail.as_ref().and_then(|t| t.protection.as_ref())
+    }
+    /// get ranges allowed to edit when the sheet is protected, None if not prescanned
+    pub fn get_protected_ranges(&self) -> Option<&Vec<ProtectedRange>> {
+        self.sheet_tail.as_ref().map(|t| &t.protected_ranges)
+    }
+    /// get the cellIs rules matched by the cell value, rules which can not be evaluated are excluded.   
+    /// with_prescan must be called before into_cached_sheet
+    pub fn get_matched_rules<A: AsRef<str>>(&self, addr: A) -> Result<Vec<&CfRule>> {
+        let (row, col) = get_tuple_from_ord(addr.as_ref().as_bytes())?;
+        let Some(tail) = &self.sheet_tail else {
+            return Err(XlsxError::InvalidState("with_prescan should be called before into_cached_sheet".to_string()));
+        };
+        let val = self.get_cell_value(addr)?;
+        Ok(tail.conditional_formats.iter()
+            .filter(|cf| cf.contains(row, col))
+            .flat_map(|cf| cf.rules.iter())
+            .filter(|rule| rule.evaluate(val) == Some(true))
+            .collect())
+    }
     /// Get all data
     pub fn get_all_cells(&self) -> &HashMap<RowNum, Vec<CellValue<'_>>> {
         &self.data
@@ -1078,12 +2870,12 @@ impl <'a> CachedSheet<'a> {
         if row >= self.top_nrow && row <= self.bottom_nrow
             && col >= self.left_ncol && col <= self.right_ncol {
             if self.data.contains_key(&row) {
-                Ok(self.data[&row].get((col-1) as usize).unwrap_or(&CellValue::Blank))
+                Ok(self.data[&row].get((col-self.left_ncol) as usize).unwrap_or(&CellValue::Blank))
             } else {
                 Ok(&CellValue::Blank)
             }
         } else {
-            Err(anyhow!("Invalid address - out of range"))
+            Err(XlsxError::InvalidCellRef(format!("{} - out of range", addr.as_ref())))
         }
     }
     /// get cell value by address, if the cell is not exist, return &CellValue::Blank
@@ -1091,14 +2883,14 @@ impl <'a> CachedSheet<'a> {
         let (row, col) = get_tuple_from_ord(addr.as_ref().as_bytes())?;
         if row >= self.top_nrow && row <= self.bottom_nrow
             && col >= self.left_ncol && col <= self.right_ncol {
-            let (merge, spans) = is_merged_cell(&self.merged_rects, row, col);
+            let (merge, spans) = self.merged_rects.is_merged_cell(row, col);
             if self.data.contains_key(&row) {
-                Ok((self.data[&row].get((col-1) as usize).unwrap_or(&CellValue::Blank), (merge, spans)))
+                Ok((self.data[&row].get((col-self.left_ncol) as usize).unwrap_or(&CellValue::Blank), (merge, spans)))
             } else {
                 Ok((&CellValue::Blank, (merge, spans)))
             }
         } else {
-            Err(anyhow!("Invalid address - out of range"))
+            Err(XlsxError::InvalidCellRef(format!("{} - out of range", addr.as_ref())))
         }
     }
 }
@@ -1134,13 +2926,13 @@ impl FromCellValue for String {
         match val {
             CellValue::Number(n) => Ok(Some(n.to_string())),
             CellValue::Date(n) => {
-                Ok(Some((BASE_DATE.clone()+(Duration::try_days(*n as i64).ok_or(anyhow!("invalid date"))?)).to_string()))
+                Ok(Some((BASE_DATE.clone()+(Duration::try_days(*n as i64).ok_or_else(|| XlsxError::conversion(val, "String"))?)).to_string()))
             },
             CellValue::Time(n) => {
                 Ok(Some(NaiveTime::from_num_seconds_from_midnight_opt(((*n-n.trunc()) * 86400.0) as u32, 0).unwrap().format("%H:%M:%S").to_string()))
             }
             CellValue::Datetime(n) => {
-                Ok(Some((BASE_DATETIME.clone()+(Duration::try_days(*n as i64).ok_or(anyhow!("invalid date"))?)+(Duration::try_seconds(((*n-n.trunc()) * 86400.0) as i64).ok_or(anyhow!("invalid date"))?)).to_string()))
+                Ok(Some((BASE_DATETIME.clone()+(Duration::try_days(*n as i64).ok_or_else(|| XlsxError::conversion(val, "String"))?)+(Duration::try_seconds(((*n-n.trunc()) * 86400.0) as i64).ok_or_else(|| XlsxError::conversion(val, "String"))?)).to_string()))
             },
             CellValue::Shared(s) => Ok(Some((**s).to_owned())),
             CellValue::String(s) => Ok(Some((*s).to_owned())),
@@ -1167,7 +2959,7 @@ impl FromCellValue for f64 {
                         } else if let Ok(n) = s.replace(',', "").parse::<f64>() {
                             Ok(Some(n))
                         } else {
-                            Err(anyhow!(format!("invalid value-{:?}", val)))
+                            Err(XlsxError::conversion(val, "f64"))
                         }
                     }
                 }
@@ -1181,12 +2973,12 @@ impl FromCellValue for f64 {
                         } else if let Ok(n) = s.replace(',', "").parse::<f64>() {
                             Ok(Some(n))
                         } else {
-                            Err(anyhow!(format!("invalid value-{:?}", val)))
+                            Err(XlsxError::conversion(val, "f64"))
                         }
                     }
                 }
             },
-            CellValue::Error(_) => Err(anyhow!(format!("invalid value-{:?}", val))),
+            CellValue::Error(_) => Err(XlsxError::conversion(val, "f64")),
             CellValue::Bool(b) => Ok(Some(if *b {1.0}else{0.0})),
             CellValue::Blank => Ok(None),
         }
@@ -1209,7 +3001,7 @@ impl FromCellValue for i64 {
                         } else if let Ok(n) = s.replace(',', "").parse::<i64>() {
                             Ok(Some(n))
                         } else {
-                            Err(anyhow!(format!("invalid value-{:?}", val)))
+                            Err(XlsxError::conversion(val, "i64"))
                         }
                     }
                 }
@@ -1223,12 +3015,12 @@ impl FromCellValue for i64 {
                         } else if let Ok(n) = s.replace(',', "").parse::<i64>() {
                             Ok(Some(n))
                         } else {
-                            Err(anyhow!(format!("invalid value-{:?}", val)))
+                            Err(XlsxError::conversion(val, "i64"))
                         }
                     }
                 }
             },
-            CellValue::Error(_) => Err(anyhow!(format!("invalid value-{:?}", val))),
+            CellValue::Error(_) => Err(XlsxError::conversion(val, "i64")),
             CellValue::Bool(b) => Ok(Some(if *b {1}else{0})),
             CellValue::Blank => Ok(None),
         }
@@ -1249,7 +3041,7 @@ impl FromCellValue for bool {
                         if NULL_STRING.contains(*s) {
                             Ok(None)
                         } else {
-                            Err(anyhow!(format!("invalid value-{:?}", val)))
+                            Err(XlsxError::conversion(val, "bool"))
                         }
                     }
                 }
@@ -1261,12 +3053,12 @@ impl FromCellValue for bool {
                         if NULL_STRING.contains(s) {
                             Ok(None)
                         } else {
-                            Err(anyhow!(format!("invalid value-{:?}", val)))
+                            Err(XlsxError::conversion(val, "bool"))
                         }
                     }
                 }
             },
-            CellValue::Error(_) => Err(anyhow!(format!("invalid value-{:?}", val))),
+            CellValue::Error(_) => Err(XlsxError::conversion(val, "bool")),
             CellValue::Bool(b) => Ok(Some(*b)),
             CellValue::Blank => Ok(None),
         }
@@ -1276,10 +3068,10 @@ impl FromCellValue for bool {
 impl FromCellValue for NaiveDate {
     fn try_from_cval(val: &CellValue<'_>) -> Result<Option<Self>> {
         match val {
-            CellValue::Number(n) => Ok(Some(BASE_DATE.clone()+(Duration::try_days(*n as i64).ok_or(anyhow!("invalid datetime"))?))),
-            CellValue::Date(n) => Ok(Some(BASE_DATE.clone()+(Duration::try_days(*n as i64).ok_or(anyhow!("invalid datetime"))?))),
-            CellValue::Time(n) => Ok(Some(BASE_DATE.clone()+(Duration::try_days(*n as i64).ok_or(anyhow!("invalid datetime"))?))),
-            CellValue::Datetime(n) => Ok(Some(BASE_DATE.clone()+(Duration::try_days(*n as i64).ok_or(anyhow!("invalid datetime"))?))),
+            CellValue::Number(n) => Ok(Some(BASE_DATE.clone()+(Duration::try_days(*n as i64).ok_or_else(|| XlsxError::conversion(val, "NaiveDate"))?))),
+            CellValue::Date(n) => Ok(Some(BASE_DATE.clone()+(Duration::try_days(*n as i64).ok_or_else(|| XlsxError::conversion(val, "NaiveDate"))?))),
+            CellValue::Time(n) => Ok(Some(BASE_DATE.clone()+(Duration::try_days(*n as i64).ok_or_else(|| XlsxError::conversion(val, "NaiveDate"))?))),
+            CellValue::Datetime(n) => Ok(Some(BASE_DATE.clone()+(Duration::try_days(*n as i64).ok_or_else(|| XlsxError::conversion(val, "NaiveDate"))?))),
             CellValue::Shared(s) => {
                 match NaiveDate::parse_from_str(*s, "%Y-%m-%d") {
                     Ok(v) => Ok(Some(v)),
@@ -1290,7 +3082,7 @@ impl FromCellValue for NaiveDate {
                                 if NULL_STRING.contains(*s) {
                                     Ok(None)
                                 } else {
-                                    Err(anyhow!(format!("invalid value-{:?}", val)))
+                                    Err(XlsxError::conversion(val, "NaiveDate"))
                                 }
                             }
                         }
@@ -1307,15 +3099,15 @@ impl FromCellValue for NaiveDate {
                                 if NULL_STRING.contains(s) {
                                     Ok(None)
                                 } else {
-                                    Err(anyhow!(format!("invalid value-{:?}", val)))
+                                    Err(XlsxError::conversion(val, "NaiveDate"))
                                 }
                             }
                         }
                     }
                 }
             },
-            CellValue::Error(_) => Err(anyhow!(format!("invalid datetime{:?}", val))),
-            CellValue::Bool(_) => Err(anyhow!(format!("invalid datetime{:?}", val))),
+            CellValue::Error(_) => Err(XlsxError::conversion(val, "NaiveDate")),
+            CellValue::Bool(_) => Err(XlsxError::conversion(val, "NaiveDate")),
             CellValue::Blank => Ok(None),
         }
     }
@@ -1325,16 +3117,16 @@ impl FromCellValue for NaiveDateTime {
     fn try_from_cval(val: &CellValue<'_>) -> Result<Option<Self>> {
         match val {
             CellValue::Number(n) => {
-                Ok(Some(BASE_DATETIME.clone()+(Duration::try_days(*n as i64).ok_or(anyhow!("invalid date"))?)+(Duration::try_seconds(((*n-n.trunc()) * 86400.0) as i64).ok_or(anyhow!("invalid date"))?)))
+                Ok(Some(BASE_DATETIME.clone()+(Duration::try_days(*n as i64).ok_or_else(|| XlsxError::conversion(val, "NaiveDateTime"))?)+(Duration::try_seconds(((*n-n.trunc()) * 86400.0) as i64).ok_or_else(|| XlsxError::conversion(val, "NaiveDateTime"))?)))
             },
             CellValue::Date(n) => {
-                Ok(Some(BASE_DATETIME.clone()+(Duration::try_days(*n as i64).ok_or(anyhow!("invalid date"))?)+(Duration::try_seconds(((*n-n.trunc()) * 86400.0) as i64).ok_or(anyhow!("invalid date"))?)))
+                Ok(Some(BASE_DATETIME.clone()+(Duration::try_days(*n as i64).ok_or_else(|| XlsxError::conversion(val, "NaiveDateTime"))?)+(Duration::try_seconds(((*n-n.trunc()) * 86400.0) as i64).ok_or_else(|| XlsxError::conversion(val, "NaiveDateTime"))?)))
             },
             CellValue::Time(n) => {
-                Ok(Some(BASE_DATETIME.clone()+(Duration::try_days(*n as i64).ok_or(anyhow!("invalid date"))?)+(Duration::try_seconds(((*n-n.trunc()) * 86400.0) as i64).ok_or(anyhow!("invalid date"))?)))
+                Ok(Some(BASE_DATETIME.clone()+(Duration::try_days(*n as i64).ok_or_else(|| XlsxError::conversion(val, "NaiveDateTime"))?)+(Duration::try_seconds(((*n-n.trunc()) * 86400.0) as i64).ok_or_else(|| XlsxError::conversion(val, "NaiveDateTime"))?)))
             },
             CellValue::Datetime(n) => {
-                Ok(Some(BASE_DATETIME.clone()+(Duration::try_days(*n as i64).ok_or(anyhow!("invalid date"))?)+(Duration::try_seconds(((*n-n.trunc()) * 86400.0) as i64).ok_or(anyhow!("invalid date"))?)))
+                Ok(Some(BASE_DATETIME.clone()+(Duration::try_days(*n as i64).ok_or_else(|| XlsxError::conversion(val, "NaiveDateTime"))?)+(Duration::try_seconds(((*n-n.trunc()) * 86400.0) as i64).ok_or_else(|| XlsxError::conversion(val, "NaiveDateTime"))?)))
             },
             CellValue::Shared(s) => {
                 match NaiveDateTime::parse_from_str(*s, "%Y-%m-%d %H:%M:%S") {
@@ -1346,7 +3138,7 @@ impl FromCellValue for NaiveDateTime {
                                 if NULL_STRING.contains(*s) {
                                     Ok(None)
                                 } else {
-                                    Err(anyhow!(format!("invalid value-{:?}", val)))
+                                    Err(XlsxError::conversion(val, "NaiveDateTime"))
                                 }
                             }
                         }
@@ -1363,15 +3155,15 @@ impl FromCellValue for NaiveDateTime {
                                 if NULL_STRING.contains(s) {
                                     Ok(None)
                                 } else {
-                                    Err(anyhow!(format!("invalid value-{:?}", val)))
+                                    Err(XlsxError::conversion(val, "NaiveDateTime"))
                                 }
                             }
                         }
                     }
                 }
             },
-            CellValue::Error(_) => Err(anyhow!(format!("invalid datetime{:?}", val))),
-            CellValue::Bool(_) => Err(anyhow!(format!("invalid datetime{:?}", val))),
+            CellValue::Error(_) => Err(XlsxError::conversion(val, "NaiveDateTime")),
+            CellValue::Bool(_) => Err(XlsxError::conversion(val, "NaiveDateTime")),
             CellValue::Blank => Ok(None),
         }
     }
@@ -1381,16 +3173,16 @@ impl FromCellValue for NaiveTime {
     fn try_from_cval(val: &CellValue<'_>) -> Result<Option<Self>> {
         match val {
             CellValue::Number(n) => {
-                Ok(Some(NaiveTime::from_num_seconds_from_midnight_opt(((*n-n.trunc()) * 86400.0) as u32, 0).ok_or(anyhow!("invalid time"))?))
+                Ok(Some(NaiveTime::from_num_seconds_from_midnight_opt(((*n-n.trunc()) * 86400.0) as u32, 0).ok_or_else(|| XlsxError::conversion(val, "NaiveTime"))?))
             },
             CellValue::Date(n) => {
-                Ok(Some(NaiveTime::from_num_seconds_from_midnight_opt(((*n-n.trunc()) * 86400.0) as u32, 0).ok_or(anyhow!("invalid time"))?))
+                Ok(Some(NaiveTime::from_num_seconds_from_midnight_opt(((*n-n.trunc()) * 86400.0) as u32, 0).ok_or_else(|| XlsxError::conversion(val, "NaiveTime"))?))
             },
             CellValue::Time(n) => {
-                Ok(Some(NaiveTime::from_num_seconds_from_midnight_opt(((*n-n.trunc()) * 86400.0) as u32, 0).ok_or(anyhow!("invalid time"))?))
+                Ok(Some(NaiveTime::from_num_seconds_from_midnight_opt(((*n-n.trunc()) * 86400.0) as u32, 0).ok_or_else(|| XlsxError::conversion(val, "NaiveTime"))?))
             },
             CellValue::Datetime(n) => {
-                Ok(Some(NaiveTime::from_num_seconds_from_midnight_opt(((*n-n.trunc()) * 86400.0) as u32, 0).ok_or(anyhow!("invalid time"))?))
+                Ok(Some(NaiveTime::from_num_seconds_from_midnight_opt(((*n-n.trunc()) * 86400.0) as u32, 0).ok_or_else(|| XlsxError::conversion(val, "NaiveTime"))?))
             },
             CellValue::Shared(s) => {
                 match NaiveTime::parse_from_str(*s, "%H:%M:%S") {
@@ -1402,7 +3194,7 @@ impl FromCellValue for NaiveTime {
                                 if NULL_STRING.contains(*s) {
                                     Ok(None)
                                 } else {
-                                    Err(anyhow!(format!("invalid value-{:?}", val)))
+                                    Err(XlsxError::conversion(val, "NaiveTime"))
                                 }
                             }
                         }
@@ -1419,15 +3211,15 @@ impl FromCellValue for NaiveTime {
                                 if NULL_STRING.contains(s) {
                                     Ok(None)
                                 } else {
-                                    Err(anyhow!(format!("invalid value-{:?}", val)))
+                                    Err(XlsxError::conversion(val, "NaiveTime"))
                                 }
                             }
                         }
                     }
                 }
             },
-            CellValue::Error(_) => Err(anyhow!(format!("invalid time{:?}", val))),
-            CellValue::Bool(_) => Err(anyhow!(format!("invalid time{:?}", val))),
+            CellValue::Error(_) => Err(XlsxError::conversion(val, "NaiveTime")),
+            CellValue::Bool(_) => Err(XlsxError::conversion(val, "NaiveTime")),
             CellValue::Blank => Ok(None),
         }
     }
@@ -1451,7 +3243,7 @@ impl FromCellValue for Date32 {
                                 if NULL_STRING.contains(*s) {
                                     Ok(None)
                                 } else {
-                                    Err(anyhow!(format!("invalid value-{:?}", val)))
+                                    Err(XlsxError::conversion(val, "Date32"))
                                 }
                             }
                         }
@@ -1468,15 +3260,15 @@ impl FromCellValue for Date32 {
                                 if NULL_STRING.contains(s) {
                                     Ok(None)
                                 } else {
-                                    Err(anyhow!(format!("invalid value-{:?}", val)))
+                                    Err(XlsxError::conversion(val, "Date32"))
                                 }
                             }
                         }
                     }
                 }
             },
-            CellValue::Error(_) => Err(anyhow!(format!("invalid date32-{:?}", val))),
-            CellValue::Bool(_) => Err(anyhow!(format!("invalid date32-{:?}", val))),
+            CellValue::Error(_) => Err(XlsxError::conversion(val, "Date32")),
+            CellValue::Bool(_) => Err(XlsxError::conversion(val, "Date32")),
             CellValue::Blank => Ok(None),
         }
     }
@@ -1500,7 +3292,7 @@ impl FromCellValue for Timestamp {
                                 if NULL_STRING.contains(*s) {
                                     Ok(None)
                                 } else {
-                                    Err(anyhow!(format!("invalid value-{:?}", val)))
+                                    Err(XlsxError::conversion(val, "Timestamp"))
                                 }
                             }
                         }
@@ -1517,15 +3309,15 @@ impl FromCellValue for Timestamp {
                                 if NULL_STRING.contains(s) {
                                     Ok(None)
                                 } else {
-                                    Err(anyhow!(format!("invalid value-{:?}", val)))
+                                    Err(XlsxError::conversion(val, "Timestamp"))
                                 }
                             }
                         }
                     }
                 }
             },
-            CellValue::Error(_) => Err(anyhow!(format!("invalid timestamp-{:?}", val))),
-            CellValue::Bool(_) => Err(anyhow!(format!("invalid timestamp-{:?}", val))),
+            CellValue::Error(_) => Err(XlsxError::conversion(val, "Timestamp")),
+            CellValue::Bool(_) => Err(XlsxError::conversion(val, "Timestamp")),
             CellValue::Blank => Ok(None),
         }
     }
@@ -1556,7 +3348,7 @@ impl FromCellValue for Timesecond {
                                 if NULL_STRING.contains(*s) {
                                     Ok(None)
                                 } else {
-                                    Err(anyhow!(format!("invalid value-{:?}", val)))
+                                    Err(XlsxError::conversion(val, "Timesecond"))
                                 }
                             }
                         }
@@ -1573,15 +3365,15 @@ impl FromCellValue for Timesecond {
                                 if NULL_STRING.contains(s) {
                                     Ok(None)
                                 } else {
-                                    Err(anyhow!(format!("invalid value-{:?}", val)))
+                                    Err(XlsxError::conversion(val, "Timesecond"))
                                 }
                             }
                         }
                     }
                 }
             },
-            CellValue::Error(_) => Err(anyhow!(format!("invalid time{:?}", val))),
-            CellValue::Bool(_) => Err(anyhow!(format!("invalid time{:?}", val))),
+            CellValue::Error(_) => Err(XlsxError::conversion(val, "Timesecond")),
+            CellValue::Bool(_) => Err(XlsxError::conversion(val, "Timesecond")),
             CellValue::Blank => Ok(None),
         }
     }
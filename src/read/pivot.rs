@@ -0,0 +1,326 @@
+use std::{cmp::max, fs::File, io::BufReader};
+use zip::{ZipArchive, read::ZipFile};
+use quick_xml::{events::Event, reader::{NsReader, Reader}};
+use crate::{error::{Result, XlsxError}, CellValue, RowNum};
+use super::{get_rel_attr, open_part, parse_w3c_datetime, read_part_rels, resolve_part, IntoCellValue, LimitedReader, ReadLimits, XlsxBook};
+
+/// field of a pivot cache
+#[derive(Debug, Clone)]
+pub struct PivotCacheField {
+    pub name: String,
+    pub num_fmt_id: Option<u32>,
+    /// shared items, which are referred by index in records
+    pub items: Vec<CellValue<'static>>,
+    /// formula of a calculated field
+    pub formula: Option<String>,
+    /// false if the field is not in the records, e.g. calculated fields
+    pub database_field: bool,
+}
+
+/// pivot cache, the records can be read by XlsxBook::get_pivot_records
+#[derive(Debug, Clone)]
+pub struct PivotCache {
+    /// cache id referred by pivot tables
+    pub cache_id: Option<u32>,
+    /// definition part, e.g. `xl/pivotCache/pivotCacheDefinition1.xml`
+    pub part: String,
+    /// records part, None if the records are not saved
+    pub records_part: Option<String>,
+    /// source sheet name
+    pub source_sheet: Option<String>,
+    /// source range, e.g. `A1:D100`
+    pub source_ref: Option<String>,
+    /// source defined name or table name
+    pub source_name: Option<String>,
+    pub record_count: Option<usize>,
+    pub fields: Vec<PivotCacheField>,
+}
+
+impl PivotCache {
+    /// names of the fields in the records
+    pub fn field_names(&self) -> Vec<&str> {
+        self.fields.iter().filter(|f| f.database_field).map(|f| f.name.as_str()).collect()
+    }
+    /// get field name by index used in pivot tables, -2 refers to the values field
+    pub fn field_name(&self, x: i32) -> Option<&str> {
+        if x == -2 {
+            return Some("Values");
+        }
+        self.fields.get(usize::try_from(x).ok()?).map(|f| f.name.as_str())
+    }
+}
+
+/// data field of a pivot table
+#[derive(Debug, Clone)]
+pub struct PivotDataField {
+    pub name: Option<String>,
+    /// index of the cache field
+    pub field: usize,
+    /// aggregate function, e.g. `sum`, `count`, `average`
+    pub subtotal: String,
+}
+
+/// layout metadata of a pivot table
+#[derive(Debug, Clone)]
+pub struct PivotTable {
+    pub name: String,
+    /// part, e.g. `xl/pivotTables/pivotTable1.xml`
+    pub part: String,
+    pub cache_id: Option<u32>,
+    /// definition part of the pivot cache
+    pub cache_part: Option<String>,
+    /// range of the pivot table, e.g. `A3:C20`
+    pub location: String,
+    /// indexes of cache fields on rows, -2 refers to the values field (see PivotCache::field_name)
+    pub row_fields: Vec<i32>,
+    /// indexes of cache fields on columns, -2 refers to the values field
+    pub col_fields: Vec<i32>,
+    /// indexes of cache fields on the filter area
+    pub page_fields: Vec<i32>,
+    pub data_fields: Vec<PivotDataField>,
+}
+
+/// parse a shared item or a record value of pivot cache: s, n, b, d, e, m
+fn parse_pivot_item(e: &quick_xml::events::BytesStart<'_>) -> Result<Option<CellValue<'static>>> {
+    let val = match e.try_get_attribute("v")? {
+        Some(v) => v.unescape_value()?.to_string(),
+        None => String::new()
+    };
+    let cval = match e.local_name().as_ref() {
+        b"s" => CellValue::String(val),
+        b"n" => CellValue::Number(val.parse()?),
+        b"b" => CellValue::Bool(val == "1" || val == "true"),
+        b"d" => match parse_w3c_datetime(&val) {
+            Some(dt) => dt.try_into_cval()?,
+            None => CellValue::String(val)
+        },
+        b"e" => CellValue::Error(val),
+        b"m" => CellValue::Blank,
+        _ => return Ok(None)
+    };
+    Ok(Some(cval))
+}
+
+/// parse pivot cache definition, records are not read
+fn read_pivot_cache(zip_archive: &mut ZipArchive<BufReader<File>>, part: &str, limits: &ReadLimits) -> Result<PivotCache> {
+    let rels = read_part_rels(zip_archive, part, limits)?;
+    let file = open_part(zip_archive, part, limits)?;
+    let mut reader = NsReader::from_reader(BufReader::new(file));
+    let mut buf = Vec::new();
+    let mut cache = PivotCache { cache_id: None, part: part.to_string(), records_part: None, source_sheet: None, source_ref: None, source_name: None, record_count: None, fields: Vec::new() };
+    let mut in_shared = false;
+    let attr_val = |e: &quick_xml::events::BytesStart<'_>, tag: &str| -> Result<Option<String>> {
+        match e.try_get_attribute(tag)? {
+            Some(v) => Ok(Some(v.unescape_value()?.to_string())),
+            None => Ok(None)
+        }
+    };
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) if e.local_name().as_ref() == b"sharedItems" => in_shared = true,
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                match e.local_name().as_ref() {
+                    b"pivotCacheDefinition" => {
+                        cache.records_part = get_rel_attr(&reader, e, "id")?.and_then(|rid| rels.get(&rid).map(|r| resolve_part(part, &r.target)));
+                        cache.record_count = attr_val(e, "recordCount")?.and_then(|v| v.parse().ok());
+                    },
+                    b"worksheetSource" => {
+                        cache.source_sheet = attr_val(e, "sheet")?;
+                        cache.source_ref = attr_val(e, "ref")?;
+                        cache.source_name = attr_val(e, "name")?;
+                    },
+                    b"cacheField" => {
+                        cache.fields.push(PivotCacheField {
+                            name: get_attr_val!(e, "name", to_string),
+                            num_fmt_id: attr_val(e, "numFmtId")?.and_then(|v| v.parse().ok()),
+                            items: Vec::new(),
+                            formula: attr_val(e, "formula")?,
+                            database_field: attr_val(e, "databaseField")?.as_deref() != Some("0"),
+                        });
+                    },
+                    _ if in_shared => {
+                        if let (Some(item), Some(field)) = (parse_pivot_item(e)?, cache.fields.last_mut()) {
+                            field.items.push(item);
+                        }
+                    },
+                    _ => ()
+                }
+            },
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"sharedItems" => in_shared = false,
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(XlsxError::from_xml(part, e)),
+            _ => ()
+        }
+        buf.clear();
+    }
+    Ok(cache)
+}
+
+/// parse pivot table definition
+fn read_pivot_table(zip_archive: &mut ZipArchive<BufReader<File>>, part: &str, limits: &ReadLimits) -> Result<PivotTable> {
+    let rels = read_part_rels(zip_archive, part, limits)?;
+    let file = open_part(zip_archive, part, limits)?;
+    let mut reader = Reader::from_reader(BufReader::new(file));
+    let mut buf = Vec::new();
+    let cache_part = rels.values().find(|r| r.is("pivotCacheDefinition")).map(|r| resolve_part(part, &r.target));
+    let mut table = PivotTable { name: String::new(), part: part.to_string(), cache_id: None, cache_part, location: String::new(), row_fields: Vec::new(), col_fields: Vec::new(), page_fields: Vec::new(), data_fields: Vec::new() };
+    let mut area = 0;    //  1-rowFields; 2-colFields
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                match e.local_name().as_ref() {
+                    b"pivotTableDefinition" => {
+                        table.name = get_attr_val!(e, "name", to_string);
+                        table.cache_id = match e.try_get_attribute("cacheId")? {
+                            Some(v) => v.unescape_value()?.parse().ok(),
+                            None => None
+                        };
+                    },
+                    b"location" => table.location = get_attr_val!(e, "ref", to_string),
+                    b"rowFields" => area = 1,
+                    b"colFields" => area = 2,
+                    b"field" if area == 1 => table.row_fields.push(get_attr_val!(e, "x", parse)),
+                    b"field" if area == 2 => table.col_fields.push(get_attr_val!(e, "x", parse)),
+                    b"pageField" => table.page_fields.push(get_attr_val!(e, "fld", parse)),
+                    b"dataField" => {
+                        table.data_fields.push(PivotDataField {
+                            name: match e.try_get_attribute("name")? {
+                                Some(v) => Some(v.unescape_value()?.to_string()),
+                                None => None
+                            },
+                            field: get_attr_val!(e, "fld", parse),
+                            subtotal: match e.try_get_attribute("subtotal")? {
+                                Some(v) => v.unescape_value()?.to_string(),
+                                None => "sum".to_string()
+                            },
+                        });
+                    },
+                    _ => ()
+                }
+            },
+            Ok(Event::End(ref e)) if matches!(e.local_name().as_ref(), b"rowFields" | b"colFields") => area = 0,
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(XlsxError::from_xml(part, e)),
+            _ => ()
+        }
+        buf.clear();
+    }
+    Ok(table)
+}
+
+/// batch reader of pivot cache records, values of shared items are resolved
+pub struct PivotRecords<'a> {
+    reader: Reader<BufReader<LimitedReader<ZipFile<'a, BufReader<File>>>>>,
+    buf: Vec<u8>,
+    part: String,
+    items: Vec<Vec<CellValue<'static>>>,    //  记录中各字段的共享项
+    iter_batch: usize,
+    count: RowNum,
+    done: bool,
+}
+
+impl<'a> PivotRecords<'a> {
+    /// read next record, record number starts from 1
+    fn next_record(&mut self) -> Result<Option<(RowNum, Vec<CellValue<'static>>)>> {
+        let mut record: Option<Vec<CellValue<'static>>> = None;
+        loop {
+            let event = match self.reader.read_event_into(&mut self.buf) {
+                Ok(Event::Empty(ref e)) if e.local_name().as_ref() == b"r" => Some(Vec::new()),
+                Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                    match (e.local_name().as_ref(), record.as_mut()) {
+                        (b"r", None) => record = Some(Vec::with_capacity(self.items.len())),
+                        (b"x", Some(r)) => {
+                            let inx: usize = get_attr_val!(e, "v", parse);
+                            let item = self.items.get(r.len()).and_then(|v| v.get(inx))
+                                .ok_or_else(|| XlsxError::MalformedXml(format!("{}: shared item {} of field {} not found", self.part, inx, r.len())))?;
+                            r.push(item.clone());
+                        },
+                        (_, Some(r)) => r.extend(parse_pivot_item(e)?),
+                        _ => ()
+                    }
+                    None
+                },
+                Ok(Event::End(ref e)) if e.local_name().as_ref() == b"r" => record.take(),
+                Ok(Event::Eof) => {
+                    self.done = true;
+                    return Ok(None);
+                },
+                Err(e) => return Err(XlsxError::from_xml(&self.part, e)),
+                _ => None
+            };
+            self.buf.clear();
+            if let Some(r) = event {
+                self.count += 1;
+                return Ok(Some((self.count, r)));
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for PivotRecords<'a> {
+    type Item = Result<(Vec<RowNum>, Vec<Vec<CellValue<'static>>>)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut nums = Vec::with_capacity(self.iter_batch);
+        let mut data = Vec::with_capacity(self.iter_batch);
+        while nums.len() < self.iter_batch {
+            match self.next_record() {
+                Ok(Some((n, r))) => {
+                    nums.push(n);
+                    data.push(r);
+                },
+                Ok(None) => break,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        if nums.is_empty() {
+            None
+        } else {
+            Some(Ok((nums, data)))
+        }
+    }
+}
+
+impl XlsxBook {
+    /// get pivot caches of the book (fields and shared items), the records can be read by get_pivot_records
+    pub fn get_pivot_caches(&mut self) -> Result<Vec<PivotCache>> {
+        let refs = self.read_workbook_refs(b"pivotCache", Some("cacheId"))?;
+        refs.into_iter().filter_map(|(cache_id, part)| part.map(|part| (cache_id, part))).map(|(cache_id, part)| {
+            let mut cache = read_pivot_cache(&mut self.zip_archive, &part, &self.options.limits)?;
+            cache.cache_id = cache_id.and_then(|v| v.parse().ok());
+            Ok(cache)
+        }).collect()
+    }
+    /// read records of the pivot cache in batches, XlsxError::MissingPart is returned if the records are not saved
+    pub fn get_pivot_records(&mut self, cache: &PivotCache, iter_batch: usize) -> Result<PivotRecords<'_>> {
+        let Some(part) = cache.records_part.clone() else {
+            return Err(XlsxError::MissingPart(format!("records of {}", cache.part)));
+        };
+        let file = open_part(&mut self.zip_archive, &part, &self.options.limits)?;
+        Ok(PivotRecords {
+            reader: Reader::from_reader(BufReader::new(file)),
+            buf: Vec::with_capacity(8*1024),
+            part,
+            items: cache.fields.iter().filter(|f| f.database_field).map(|f| f.items.clone()).collect(),
+            iter_batch: max(iter_batch, 1),
+            count: 0,
+            done: false,
+        })
+    }
+    /// get layout metadata of the pivot tables in the sheet
+    pub fn get_pivot_tables(&mut self, sht_name: &str) -> Result<Vec<PivotTable>> {
+        let Some(part) = self.map_sheet.get(sht_name).cloned() else {
+            return Err(XlsxError::SheetNotFound(sht_name.to_string()));
+        };
+        let limits = &self.options.limits;
+        let sheet_rels = read_part_rels(&mut self.zip_archive, &part, limits)?;
+        let mut parts: Vec<String> = sheet_rels.values().filter(|r| r.is("pivotTable") && !r.external).map(|r| resolve_part(&part, &r.target)).collect();
+        parts.sort();
+        parts.iter().map(|p| read_pivot_table(&mut self.zip_archive, p, limits)).collect()
+    }
+}
@@ -0,0 +1,132 @@
+use std::{collections::HashMap, fs::File, io::BufReader};
+use zip::ZipArchive;
+use chrono::NaiveDateTime;
+use quick_xml::{events::Event, reader::Reader};
+use crate::error::{Result, XlsxError};
+use super::{open_part, parse_w3c_datetime, ReadLimits, XlsxBook};
+
+/// typed value of a custom property
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    String(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Datetime(NaiveDateTime),
+}
+
+/// document properties of the book, from docProps/core.xml, docProps/app.xml and docProps/custom.xml
+#[derive(Debug, Clone, Default)]
+pub struct BookProperties {
+    pub title: Option<String>,
+    pub subject: Option<String>,
+    pub creator: Option<String>,
+    pub last_modified_by: Option<String>,
+    /// created time (UTC)
+    pub created: Option<NaiveDateTime>,
+    /// modified time (UTC)
+    pub modified: Option<NaiveDateTime>,
+    pub application: Option<String>,
+    pub company: Option<String>,
+    /// custom properties by name
+    pub custom: HashMap<String, PropertyValue>,
+}
+
+/// parse a custom property value by the vt type
+fn parse_property_value(vt: &[u8], text: String) -> PropertyValue {
+    let parsed = match vt {
+        b"i1" | b"i2" | b"i4" | b"i8" | b"int" | b"ui1" | b"ui2" | b"ui4" | b"ui8" | b"uint" => text.trim().parse().ok().map(PropertyValue::Int),
+        b"r4" | b"r8" | b"decimal" => text.trim().parse().ok().map(PropertyValue::Float),
+        b"bool" => match text.trim() {
+            "true" | "1" => Some(PropertyValue::Bool(true)),
+            "false" | "0" => Some(PropertyValue::Bool(false)),
+            _ => None
+        },
+        b"filetime" | b"date" => parse_w3c_datetime(text.trim()).map(PropertyValue::Datetime),
+        _ => None
+    };
+    parsed.unwrap_or(PropertyValue::String(text))
+}
+
+/// read text of the leaf elements by local name (the last one wins), None if the part not exists
+fn read_part_texts(zip_archive: &mut ZipArchive<BufReader<File>>, part: &str, limits: &ReadLimits) -> Result<Option<HashMap<Vec<u8>, String>>> {
+    let file = match open_part(zip_archive, part, limits) {
+        Ok(file) => file,
+        Err(XlsxError::MissingPart(_)) => return Ok(None),
+        Err(e) => return Err(e)
+    };
+    let mut reader = Reader::from_reader(BufReader::new(file));
+    let mut buf = Vec::new();
+    let mut texts = HashMap::new();
+    let mut name = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) => {
+                name = e.local_name().as_ref().to_vec();
+            },
+            Ok(Event::Text(ref t)) if !name.is_empty() => {
+                texts.insert(name.clone(), t.unescape()?.to_string());
+            },
+            Ok(Event::End(_)) => {
+                name.clear();
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(XlsxError::from_xml(part, e)),
+            _ => ()
+        }
+        buf.clear();
+    }
+    Ok(Some(texts))
+}
+
+impl XlsxBook {
+    /// get document properties (title, creator, created time, custom properties, etc.), missing parts are ignored
+    pub fn get_properties(&mut self) -> Result<BookProperties> {
+        let mut props = BookProperties::default();
+        if let Some(mut texts) = read_part_texts(&mut self.zip_archive, "docProps/core.xml", &self.options.limits)? {
+            props.title = texts.remove(b"title".as_ref());
+            props.subject = texts.remove(b"subject".as_ref());
+            props.creator = texts.remove(b"creator".as_ref());
+            props.last_modified_by = texts.remove(b"lastModifiedBy".as_ref());
+            props.created = texts.get(b"created".as_ref()).and_then(|v| parse_w3c_datetime(v.trim()));
+            props.modified = texts.get(b"modified".as_ref()).and_then(|v| parse_w3c_datetime(v.trim()));
+        }
+        if let Some(mut texts) = read_part_texts(&mut self.zip_archive, "docProps/app.xml", &self.options.limits)? {
+            props.application = texts.remove(b"Application".as_ref());
+            props.company = texts.remove(b"Company".as_ref());
+        }
+        let file = match open_part(&mut self.zip_archive, "docProps/custom.xml", &self.options.limits) {
+            Ok(file) => file,
+            Err(XlsxError::MissingPart(_)) => return Ok(props),
+            Err(e) => return Err(e)
+        };
+        let mut reader = Reader::from_reader(BufReader::new(file));
+        let mut buf = Vec::new();
+        let (mut name, mut vt, mut text) = (None, Vec::new(), String::new());
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) => {
+                    if e.local_name().as_ref() == b"property" {
+                        name = Some(get_attr_val!(e, "name", to_string));
+                    } else if name.is_some() {
+                        vt = e.local_name().as_ref().to_vec();    //  vt:lpwstr, vt:i4, vt:bool, vt:filetime 等
+                        text.clear();
+                    }
+                },
+                Ok(Event::Text(ref t)) if name.is_some() => {
+                    text += &t.unescape()?;
+                },
+                Ok(Event::End(ref e)) if e.local_name().as_ref() == b"property" => {
+                    if let Some(name) = name.take() {
+                        props.custom.insert(name, parse_property_value(&vt, std::mem::take(&mut text)));
+                    }
+                },
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(XlsxError::from_xml("custom.xml", e)),
+                _ => ()
+            }
+            buf.clear();
+        }
+        Ok(props)
+    }
+}
@@ -0,0 +1,219 @@
+use std::io::BufReader;
+use quick_xml::{events::Event, reader::Reader};
+use crate::{error::{Result, XlsxError}, get_tuple_from_ord, ColNum, MergedRange, RowNum};
+use super::{attr_bool, open_part, XlsxBook};
+
+/// password hash of the protection, only the hash metadata is stored in the file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PasswordHash {
+    /// hash algorithm, e.g. `SHA-512`
+    pub algorithm_name: Option<String>,
+    /// base64 encoded hash value
+    pub hash_value: Option<String>,
+    /// base64 encoded salt value
+    pub salt_value: Option<String>,
+    /// iterations of the hash
+    pub spin_count: Option<u32>,
+    /// legacy 16-bit password hash in hex, e.g. `CC3D`
+    pub legacy_hash: Option<String>,
+}
+
+impl PasswordHash {
+    /// read hash attributes with the prefix (e.g. `workbook` for `workbookHashValue`), None if no password set
+    pub(super) fn from_attrs(e: &quick_xml::events::BytesStart<'_>, prefix: &str) -> Result<Option<Self>> {
+        let attr = |name: &str| -> Result<Option<String>> {
+            let tag = if prefix.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}{}{}", prefix, name[..1].to_ascii_uppercase(), &name[1..])
+            };
+            match e.try_get_attribute(tag.as_str())? {
+                Some(v) => Ok(Some(v.unescape_value()?.to_string())),
+                None => Ok(None)
+            }
+        };
+        let hash = PasswordHash {
+            algorithm_name: attr("algorithmName")?,
+            hash_value: attr("hashValue")?,
+            salt_value: attr("saltValue")?,
+            spin_count: attr("spinCount")?.and_then(|v| v.parse().ok()),
+            legacy_hash: attr("password")?,
+        };
+        Ok(if hash.hash_value.is_some() || hash.legacy_hash.is_some() {Some(hash)} else {None})
+    }
+}
+
+/// sheet protection, the flags are true if the action is disallowed when the sheet is protected
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetProtection {
+    /// the sheet is protected
+    pub sheet: bool,
+    pub password: Option<PasswordHash>,
+    pub objects: bool,
+    pub scenarios: bool,
+    pub format_cells: bool,
+    pub format_columns: bool,
+    pub format_rows: bool,
+    pub insert_columns: bool,
+    pub insert_rows: bool,
+    pub insert_hyperlinks: bool,
+    pub delete_columns: bool,
+    pub delete_rows: bool,
+    pub select_locked_cells: bool,
+    pub sort: bool,
+    pub auto_filter: bool,
+    pub pivot_tables: bool,
+    pub select_unlocked_cells: bool,
+}
+
+impl SheetProtection {
+    pub(super) fn from_attrs(e: &quick_xml::events::BytesStart<'_>) -> Result<Self> {
+        Ok(SheetProtection {
+            sheet: attr_bool(e, "sheet", false)?,
+            password: PasswordHash::from_attrs(e, "")?,
+            objects: attr_bool(e, "objects", false)?,
+            scenarios: attr_bool(e, "scenarios", false)?,
+            format_cells: attr_bool(e, "formatCells", true)?,
+            format_columns: attr_bool(e, "formatColumns", true)?,
+            format_rows: attr_bool(e, "formatRows", true)?,
+            insert_columns: attr_bool(e, "insertColumns", true)?,
+            insert_rows: attr_bool(e, "insertRows", true)?,
+            insert_hyperlinks: attr_bool(e, "insertHyperlinks", true)?,
+            delete_columns: attr_bool(e, "deleteColumns", true)?,
+            delete_rows: attr_bool(e, "deleteRows", true)?,
+            select_locked_cells: attr_bool(e, "selectLockedCells", false)?,
+            sort: attr_bool(e, "sort", true)?,
+            auto_filter: attr_bool(e, "autoFilter", true)?,
+            pivot_tables: attr_bool(e, "pivotTables", true)?,
+            select_unlocked_cells: attr_bool(e, "selectUnlockedCells", false)?,
+        })
+    }
+}
+
+/// range which users are allowed to edit when the sheet is protected
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtectedRange {
+    pub name: String,
+    /// original sqref, e.g. `A1:B3 D5`
+    pub sqref: String,
+    pub ranges: Vec<MergedRange>,
+    /// password required to edit the range
+    pub password: Option<PasswordHash>,
+    /// windows security descriptor of the users allowed to edit without password
+    pub security_descriptor: Option<String>,
+}
+
+impl ProtectedRange {
+    /// whether the cell is in the range
+    pub fn contains(&self, row: RowNum, col: ColNum) -> bool {
+        self.ranges.iter().any(|((r1, c1), (r2, c2))| row >= *r1 && row <= *r2 && col >= *c1 && col <= *c2)
+    }
+}
+
+/// workbook protection in workbook.xml
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkbookProtection {
+    pub lock_structure: bool,
+    pub lock_windows: bool,
+    pub lock_revision: bool,
+    pub workbook_password: Option<PasswordHash>,
+    pub revisions_password: Option<PasswordHash>,
+}
+
+/// protection of the cell format (xf) in styles.xml, it takes effect only when the sheet is protected
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellProtection {
+    pub locked: bool,
+    /// the formula is hidden
+    pub hidden: bool,
+}
+
+impl Default for CellProtection {
+    fn default() -> Self {
+        CellProtection { locked: true, hidden: false }
+    }
+}
+
+/// cells of the sheet with an unlocked format, which are editable when the sheet is protected
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UnlockedCells {
+    /// cells with an unlocked format (attribute `s` of the cell)
+    pub cells: Vec<(RowNum, ColNum)>,
+    /// column ranges (min, max) with an unlocked format (`<col style=..>`), applied to the cells without their own format
+    pub columns: Vec<(ColNum, ColNum)>,
+    /// row ranges (first, last) with an unlocked format (`<row s=.. customFormat="1">`), applied to the cells without their own format
+    pub rows: Vec<(RowNum, RowNum)>,
+}
+
+impl UnlockedCells {
+    /// check whether no cell, column or row is unlocked
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty() && self.columns.is_empty() && self.rows.is_empty()
+    }
+}
+
+impl XlsxBook {
+    /// get workbook protection, None if not set
+    pub fn get_workbook_protection(&self) -> Option<&WorkbookProtection> {
+        self.book_protection.as_ref()
+    }
+    /// get protection of the cell format by the style index (attribute `s` of the cell), cells are locked by default
+    pub fn get_cell_protection(&self, style: u32) -> CellProtection {
+        self.cell_protections.get(style as usize).copied().unwrap_or_default()
+    }
+    /// get unlocked cells, columns and rows of the sheet by the formats, which are editable when the sheet is protected.   
+    /// only the attributes are scanned, at the cost of one extra decompression pass
+    pub fn get_unlocked_cells(&mut self, sht_name: &str) -> Result<UnlockedCells> {
+        let Some(part) = self.map_sheet.get(sht_name).cloned() else {
+            return Err(XlsxError::SheetNotFound(sht_name.to_string()));
+        };
+        let mut unlocked = UnlockedCells::default();
+        if self.cell_protections.iter().all(|p| p.locked) {
+            return Ok(unlocked);
+        }
+        let is_unlocked = |style: u32| self.cell_protections.get(style as usize).is_some_and(|p| !p.locked);
+        let file = open_part(&mut self.zip_archive, &part, &self.options.limits)?;
+        let mut reader = Reader::from_reader(BufReader::new(file));
+        let mut buf = Vec::with_capacity(8*1024);
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                    match e.local_name().as_ref() {
+                        b"c" => {
+                            if let (Some(s), Some(r)) = (e.try_get_attribute("s")?, e.try_get_attribute("r")?) {
+                                if is_unlocked(s.unescape_value()?.parse()?) {
+                                    unlocked.cells.push(get_tuple_from_ord(r.unescape_value()?.as_bytes())?);
+                                }
+                            }
+                        },
+                        b"row" => {
+                            if let (Some(s), Some(r), true) = (e.try_get_attribute("s")?, e.try_get_attribute("r")?, attr_bool(e, "customFormat", false)?) {
+                                let row: RowNum = r.unescape_value()?.parse()?;
+                                if is_unlocked(s.unescape_value()?.parse()?) {
+                                    //  相邻的行合并为一个区域
+                                    match unlocked.rows.last_mut() {
+                                        Some(last) if last.1 + 1 == row => last.1 = row,
+                                        _ => unlocked.rows.push((row, row))
+                                    }
+                                }
+                            }
+                        },
+                        b"col" => {
+                            if let Some(s) = e.try_get_attribute("style")? {
+                                if is_unlocked(s.unescape_value()?.parse()?) {
+                                    unlocked.columns.push((get_attr_val!(e, "min", parse), get_attr_val!(e, "max", parse)));
+                                }
+                            }
+                        },
+                        _ => ()
+                    }
+                },
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(XlsxError::from_xml(&part, e)),
+                _ => ()
+            }
+            buf.clear();
+        }
+        Ok(unlocked)
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{error::Result, CellValue, ColNum, MergedRange, RowNum};
+use super::parse_sqref;
+
+/// type of data validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationKind {
+    Any,
+    List,
+    Whole,
+    Decimal,
+    Date,
+    Time,
+    TextLength,
+    Custom,
+}
+
+/// operator of data validation (whole, decimal, date, time and textLength) and conditional formatting (cellIs)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationOperator {
+    Between,
+    NotBetween,
+    Equal,
+    NotEqual,
+    GreaterThan,
+    LessThan,
+    GreaterThanOrEqual,
+    LessThanOrEqual,
+}
+
+impl ValidationOperator {
+    /// parse from the attribute value, between by default
+    pub(super) fn from_attr(val: &str) -> Self {
+        match val {
+            "notBetween" => Self::NotBetween,
+            "equal" => Self::Equal,
+            "notEqual" => Self::NotEqual,
+            "greaterThan" => Self::GreaterThan,
+            "lessThan" => Self::LessThan,
+            "greaterThanOrEqual" => Self::GreaterThanOrEqual,
+            "lessThanOrEqual" => Self::LessThanOrEqual,
+            _ => Self::Between
+        }
+    }
+    /// compare n with v1 (and v2 for between and notBetween)
+    pub(super) fn compare(self, n: f64, v1: f64, v2: Option<f64>) -> bool {
+        match self {
+            Self::Between => n >= v1 && n <= v2.unwrap_or(v1),
+            Self::NotBetween => n < v1 || n > v2.unwrap_or(v1),
+            Self::Equal => n == v1,
+            Self::NotEqual => n != v1,
+            Self::GreaterThan => n > v1,
+            Self::LessThan => n < v1,
+            Self::GreaterThanOrEqual => n >= v1,
+            Self::LessThanOrEqual => n <= v1,
+        }
+    }
+}
+
+/// data validation rule of cell ranges
+#[derive(Debug, Clone)]
+pub struct DataValidation {
+    pub kind: ValidationKind,
+    pub operator: ValidationOperator,
+    /// ranges, e.g. `A1:A10 C1`
+    pub sqref: String,
+    pub ranges: Vec<MergedRange>,
+    /// formula or value, e.g. `"Yes,No"`, `$D$1:$D$5`, `Sheet2!$A$1:$A$3`, `10`
+    pub formula1: Option<String>,
+    pub formula2: Option<String>,
+    /// values of inline list, e.g. `"Yes,No"`. None if the list refers to a range
+    pub list_values: Option<Vec<String>>,
+    pub allow_blank: bool,
+}
+
+impl DataValidation {
+    /// parse the attributes of dataValidation
+    pub(super) fn from_attrs(e: &quick_xml::events::BytesStart<'_>) -> Result<Self> {
+        let kind = match e.try_get_attribute("type")? {
+            Some(v) => match v.unescape_value()?.as_ref() {
+                "list" => ValidationKind::List,
+                "whole" => ValidationKind::Whole,
+                "decimal" => ValidationKind::Decimal,
+                "date" => ValidationKind::Date,
+                "time" => ValidationKind::Time,
+                "textLength" => ValidationKind::TextLength,
+                "custom" => ValidationKind::Custom,
+                _ => ValidationKind::Any
+            },
+            None => ValidationKind::Any
+        };
+        let operator = match e.try_get_attribute("operator")? {
+            Some(v) => ValidationOperator::from_attr(&v.unescape_value()?),
+            None => ValidationOperator::Between
+        };
+        let allow_blank = match e.try_get_attribute("allowBlank")? {
+            Some(v) => matches!(v.unescape_value()?.as_ref(), "1" | "true"),
+            None => false
+        };
+        let sqref = match e.try_get_attribute("sqref")? {
+            Some(v) => v.unescape_value()?.to_string(),
+            None => String::new()    //  x14扩展中sqref为子元素
+        };
+        Ok(Self { kind, operator, sqref, ranges: Vec::new(), formula1: None, formula2: None, list_values: None, allow_blank })
+    }
+    /// parse ranges and inline list after all children read
+    pub(super) fn finish(&mut self) -> Result<()> {
+        self.ranges = parse_sqref(&self.sqref)?;
+        if self.kind == ValidationKind::List {
+            self.list_values = self.formula1.as_deref()
+                .and_then(|f| f.trim().strip_prefix('"')?.strip_suffix('"').map(|v| v.split(',').map(|s| s.trim().to_string()).collect()));
+        }
+        Ok(())
+    }
+    /// check the value, returns the reason if violated. blank cells, custom formulas and lists referring to ranges are not checked
+    pub fn check(&self, val: &CellValue<'_>) -> Option<String> {
+        let number = match val {
+            CellValue::Number(n) | CellValue::Date(n) | CellValue::Time(n) | CellValue::Datetime(n) => Some(*n),
+            _ => None
+        };
+        let text = match val {
+            CellValue::Blank => return None,
+            CellValue::Shared(s) => Some(s.to_string()),
+            CellValue::String(s) => Some(s.clone()),
+            CellValue::Bool(b) => Some(if *b {"TRUE".to_string()} else {"FALSE".to_string()}),
+            _ => number.map(|n| n.to_string())
+        };
+        let bound = |f: &Option<String>| f.as_deref().and_then(|v| v.trim().parse::<f64>().ok());
+        let (v1, v2) = (bound(&self.formula1), bound(&self.formula2));
+        let target = match self.kind {
+            ValidationKind::Any | ValidationKind::Custom => return None,
+            ValidationKind::List => {
+                let list = self.list_values.as_ref()?;
+                return match text {
+                    Some(t) if list.contains(&t) => None,
+                    _ => Some(format!("not in list {}", list.join(",")))
+                };
+            },
+            ValidationKind::TextLength => text.map(|t| t.chars().count() as f64),
+            ValidationKind::Whole => match number {
+                Some(n) if n.fract() == 0.0 => Some(n),
+                _ => return Some("not a whole number".to_string())
+            },
+            _ => match number {
+                Some(n) => Some(n),
+                None => return Some("not a number".to_string())
+            }
+        };
+        let (Some(n), Some(v1)) = (target, v1) else {
+            return None;    //  公式无法计算时不检查
+        };
+        if self.operator.compare(n, v1, v2) {
+            None
+        } else {
+            Some(format!("{:?} {:?} {}{} failed", self.kind, self.operator, v1, v2.map(|v| format!(" and {}", v)).unwrap_or_default()))
+        }
+    }
+}
+
+/// cell value violating the data validation rule
+#[derive(Debug, Clone)]
+pub struct ValidationViolation {
+    pub row: RowNum,
+    pub col: ColNum,
+    pub value: CellValue<'static>,
+    /// sqref of the rule
+    pub sqref: String,
+    pub reason: String,
+}
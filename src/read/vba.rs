@@ -0,0 +1,308 @@
+// vbaProject.bin: https://learn.microsoft.com/en-us/openspecs/office_file_formats/ms-ovba/
+use std::collections::{HashMap, HashSet};
+
+use crate::error::{Result, XlsxError};
+
+const END_OF_CHAIN: u32 = 0xFFFF_FFFE;
+const MAX_SECTOR: u32 = 0xFFFF_FFFA;
+
+fn malformed(msg: &str) -> XlsxError {
+    XlsxError::MalformedVba(msg.to_string())
+}
+
+fn read_u16(data: &[u8], pos: usize) -> Result<u16> {
+    data.get(pos..pos+2).map(|v| u16::from_le_bytes([v[0], v[1]])).ok_or_else(|| malformed("unexpected end of data"))
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Result<u32> {
+    data.get(pos..pos+4).map(|v| u32::from_le_bytes([v[0], v[1], v[2], v[3]])).ok_or_else(|| malformed("unexpected end of data"))
+}
+
+/// directory entry of the compound file
+struct DirEntry {
+    name: String,
+    kind: u8,    //  1-storage; 2-stream; 5-root
+    left: u32,
+    right: u32,
+    child: u32,
+    start: u32,
+    size: u64,
+}
+
+/// compound file binary (MS-CFB) reader, the whole file is kept in memory
+struct Cfb<'a> {
+    data: &'a [u8],
+    sector_size: usize,
+    fat: Vec<u32>,
+    mini_fat: Vec<u32>,
+    mini_stream: Vec<u8>,
+    mini_cutoff: u64,
+    entries: Vec<DirEntry>,
+    paths: HashMap<String, usize>,    //  小写路径，如 vba/dir
+}
+
+impl<'a> Cfb<'a> {
+    fn new(data: &'a [u8]) -> Result<Self> {
+        if data.len() < 512 || data[..8] != [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1] {
+            return Err(malformed("invalid compound file signature"));
+        }
+        let major_version = read_u16(data, 0x1A)?;
+        let sector_shift = read_u16(data, 0x1E)?;
+        if sector_shift != 9 && sector_shift != 12 {
+            return Err(malformed("invalid sector size"));
+        }
+        let mut cfb = Cfb {
+            data,
+            sector_size: 1 << sector_shift,
+            fat: Vec::new(),
+            mini_fat: Vec::new(),
+            mini_stream: Vec::new(),
+            mini_cutoff: read_u32(data, 0x38)? as u64,
+            entries: Vec::new(),
+            paths: HashMap::new(),
+        };
+        //  DIFAT: 文件头中的109项，以及DIFAT扇区链
+        let mut fat_sectors = Vec::new();
+        for i in 0..109 {
+            let id = read_u32(data, 0x4C + i*4)?;
+            if id <= MAX_SECTOR {
+                fat_sectors.push(id);
+            }
+        }
+        let (mut difat, mut ndifat) = (read_u32(data, 0x44)?, read_u32(data, 0x48)?);
+        while difat <= MAX_SECTOR && ndifat > 0 {
+            let sector = cfb.sector(difat)?;
+            let per = cfb.sector_size / 4 - 1;
+            for i in 0..per {
+                let id = read_u32(sector, i*4)?;
+                if id <= MAX_SECTOR {
+                    fat_sectors.push(id);
+                }
+            }
+            difat = read_u32(sector, per*4)?;
+            ndifat -= 1;
+        }
+        let mut fat = Vec::with_capacity(fat_sectors.len() * cfb.sector_size / 4);
+        for id in fat_sectors {
+            let sector = cfb.sector(id)?;
+            fat.extend(sector.chunks_exact(4).map(|v| u32::from_le_bytes([v[0], v[1], v[2], v[3]])));
+        }
+        cfb.fat = fat;
+        let dir = cfb.read_chain(read_u32(data, 0x30)?, None)?;
+        cfb.entries = dir.chunks_exact(128).map(|v| {
+            let len = std::cmp::min(u16::from_le_bytes([v[64], v[65]]) as usize, 64).saturating_sub(2);
+            let name: Vec<u16> = v[..len].chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+            //  版本3的文件，大小的高32位可能不为0，应忽略
+            let size = u64::from_le_bytes([v[120], v[121], v[122], v[123], v[124], v[125], v[126], v[127]]);
+            DirEntry {
+                name: String::from_utf16_lossy(&name),
+                kind: v[66],
+                left: u32::from_le_bytes([v[68], v[69], v[70], v[71]]),
+                right: u32::from_le_bytes([v[72], v[73], v[74], v[75]]),
+                child: u32::from_le_bytes([v[76], v[77], v[78], v[79]]),
+                start: u32::from_le_bytes([v[116], v[117], v[118], v[119]]),
+                size: if major_version == 3 {size & 0xFFFF_FFFF} else {size},
+            }
+        }).collect();
+        let root = cfb.entries.first().filter(|v| v.kind == 5).ok_or_else(|| malformed("root entry not found"))?;
+        let (root_start, root_size, root_child) = (root.start, root.size, root.child);
+        cfb.mini_stream = cfb.read_chain(root_start, Some(root_size))?;
+        let mini_fat = cfb.read_chain(read_u32(data, 0x3C)?, None)?;
+        cfb.mini_fat = mini_fat.chunks_exact(4).map(|v| u32::from_le_bytes([v[0], v[1], v[2], v[3]])).collect();
+        cfb.index_paths(root_child)?;
+        Ok(cfb)
+    }
+    fn sector(&self, id: u32) -> Result<&'a [u8]> {
+        let start = (id as usize + 1) * self.sector_size;
+        self.data.get(start..start+self.sector_size).ok_or_else(|| malformed("sector out of range"))
+    }
+    /// read the sector chain from start, truncated to size if given
+    fn read_chain(&self, start: u32, size: Option<u64>) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        let mut id = start;
+        while id != END_OF_CHAIN && id <= MAX_SECTOR {
+            out.extend_from_slice(self.sector(id)?);
+            if out.len() > self.data.len() {
+                return Err(malformed("sector chain is looped"));
+            }
+            id = *self.fat.get(id as usize).ok_or_else(|| malformed("sector out of fat"))?;
+        }
+        if let Some(size) = size {
+            out.truncate(size as usize);
+        }
+        Ok(out)
+    }
+    /// read the mini sector chain from start
+    fn read_mini_chain(&self, start: u32, size: u64) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        let mut id = start;
+        while id != END_OF_CHAIN && id <= MAX_SECTOR && (out.len() as u64) < size {
+            let pos = id as usize * 64;
+            out.extend_from_slice(self.mini_stream.get(pos..pos+64).ok_or_else(|| malformed("mini sector out of range"))?);
+            id = *self.mini_fat.get(id as usize).ok_or_else(|| malformed("mini sector out of fat"))?;
+        }
+        out.truncate(size as usize);
+        Ok(out)
+    }
+    /// index the paths of the entries in the tree of siblings, walked with an explicit stack
+    fn index_paths(&mut self, root_child: u32) -> Result<()> {
+        let mut stack = vec![(root_child, String::new())];
+        let mut visited = HashSet::new();
+        while let Some((id, parent)) = stack.pop() {
+            if id > MAX_SECTOR {
+                continue;
+            }
+            if !visited.insert(id) {
+                return Err(malformed("directory tree is looped"));
+            }
+            let entry = self.entries.get(id as usize).ok_or_else(|| malformed("directory entry out of range"))?;
+            let path = format!("{}{}", parent, entry.name.to_lowercase());
+            if entry.kind == 1 {
+                stack.push((entry.child, format!("{}/", path)));
+            }
+            stack.push((entry.left, parent.clone()));
+            stack.push((entry.right, parent));
+            self.paths.insert(path, id as usize);
+        }
+        Ok(())
+    }
+    /// read stream by path, e.g. `VBA/dir`, case insensitive
+    fn read_stream(&self, path: &str) -> Result<Vec<u8>> {
+        let id = *self.paths.get(&path.to_lowercase()).ok_or_else(|| malformed(&format!("stream {} not found", path)))?;
+        let entry = &self.entries[id];
+        if entry.size < self.mini_cutoff {
+            self.read_mini_chain(entry.start, entry.size)
+        } else {
+            self.read_chain(entry.start, Some(entry.size))
+        }
+    }
+}
+
+/// decompress the compressed container (MS-OVBA 2.4.1)
+fn decompress(data: &[u8]) -> Result<Vec<u8>> {
+    if data.first() != Some(&1) {
+        return Err(malformed("invalid compressed container signature"));
+    }
+    let mut out = Vec::with_capacity(data.len() * 2);
+    let mut pos = 1;
+    while pos + 2 <= data.len() {
+        let header = read_u16(data, pos)?;
+        let chunk_end = std::cmp::min(pos + (header & 0x0FFF) as usize + 3, data.len());
+        pos += 2;
+        if header & 0x8000 == 0 {
+            //  未压缩的块固定4096字节
+            let end = std::cmp::min(pos + 4096, data.len());
+            out.extend_from_slice(&data[pos..end]);
+            pos = end;
+            continue;
+        }
+        let chunk_start = out.len();
+        while pos < chunk_end {
+            let flags = data[pos];
+            pos += 1;
+            for bit in 0..8 {
+                if pos >= chunk_end {
+                    break;
+                }
+                if flags & (1 << bit) == 0 {
+                    out.push(data[pos]);
+                    pos += 1;
+                } else {
+                    let token = read_u16(data, pos)? as usize;
+                    pos += 2;
+                    let diff = out.len() - chunk_start;
+                    let mut bit_count = 4;
+                    while (1 << bit_count) < diff {
+                        bit_count += 1;
+                    }
+                    let length_mask = 0xFFFF >> bit_count;
+                    let length = (token & length_mask) + 3;
+                    let offset = (token >> (16 - bit_count)) + 1;
+                    if offset > diff {
+                        return Err(malformed("invalid copy token"));
+                    }
+                    let from = out.len() - offset;
+                    for i in 0..length {
+                        out.push(out[from + i]);
+                    }
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// type of VBA module
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VbaModuleKind {
+    /// standard module
+    Procedural,
+    /// document (e.g. ThisWorkbook, Sheet1), class or form module
+    Document,
+}
+
+/// VBA module with source code
+#[derive(Debug, Clone)]
+pub struct VbaModule {
+    pub name: String,
+    /// name of the stream in the VBA storage
+    pub stream_name: String,
+    pub kind: VbaModuleKind,
+    /// source code, decoded by the code page of the project
+    pub code: String,
+}
+
+/// VBA project in vbaProject.bin
+#[derive(Debug, Clone)]
+pub struct VbaProject {
+    pub name: String,
+    /// code page of the module names and source code, e.g. 1252, 936
+    pub code_page: u16,
+    pub modules: Vec<VbaModule>,
+}
+
+/// parse vbaProject.bin into modules with source code
+pub(crate) fn parse_vba_project(data: &[u8]) -> Result<VbaProject> {
+    let cfb = Cfb::new(data)?;
+    let dir = decompress(&cfb.read_stream("VBA/dir")?)?;
+    let mut project = VbaProject { name: String::new(), code_page: 1252, modules: Vec::new() };
+    //  模块记录: (名称, 流名称, 类型, 源代码偏移)
+    let mut module: Option<(Vec<u8>, Vec<u8>, VbaModuleKind, usize)> = None;
+    let mut records = Vec::new();
+    let mut pos = 0;
+    while pos + 6 <= dir.len() {
+        let id = read_u16(&dir, pos)?;
+        let size = read_u32(&dir, pos+2)? as usize;
+        //  PROJECTVERSION的size字段为保留值4，实际数据为6字节
+        let size = if id == 0x0009 {6} else {size};
+        let body = dir.get(pos+6..pos+6+size).ok_or_else(|| malformed("dir record out of range"))?;
+        pos += 6 + size;
+        match id {
+            0x0003 => project.code_page = read_u16(body, 0)?,
+            0x0004 => project.name = decode(body, project.code_page),
+            0x0019 => module = Some((body.to_vec(), body.to_vec(), VbaModuleKind::Procedural, 0)),
+            0x001A => if let Some(m) = module.as_mut() { m.1 = body.to_vec() },
+            0x0021 => if let Some(m) = module.as_mut() { m.2 = VbaModuleKind::Procedural },
+            0x0022 => if let Some(m) = module.as_mut() { m.2 = VbaModuleKind::Document },
+            0x0031 => if let Some(m) = module.as_mut() { m.3 = read_u32(body, 0)? as usize },
+            0x002B => records.extend(module.take()),
+            _ => ()
+        }
+    }
+    for (name, stream_name, kind, offset) in records {
+        let stream_name = decode(&stream_name, project.code_page);
+        let stream = cfb.read_stream(&format!("VBA/{}", stream_name))?;
+        let source = stream.get(offset..).ok_or_else(|| malformed("module offset out of range"))?;
+        let code = decode(&decompress(source)?, project.code_page);
+        project.modules.push(VbaModule { name: decode(&name, project.code_page), stream_name, kind, code });
+    }
+    Ok(project)
+}
+
+/// decode MBCS text by the code page
+fn decode(text: &[u8], code_page: u16) -> String {
+    match codepage::to_encoding(code_page) {
+        Some(enc) => enc.decode(text).0.to_string(),
+        None => String::from_utf8_lossy(text).to_string()
+    }
+}
@@ -0,0 +1,134 @@
+use std::{future::Future, pin::Pin, task::{Context, Poll}};
+use futures_core::Stream;
+use tokio::{io::{AsyncRead, AsyncWriteExt}, sync::mpsc::{self, Receiver}, task::JoinHandle};
+
+use crate::{error::{Result, XlsxError}, read::{temp_file_path, ReadOptions, TempFile, XlsxBook, XlsxSheet}, CellValue, ColNum, RowNum};
+
+/// owned batch of rows: (row numbers, rows)
+pub type Batch = (Vec<RowNum>, Vec<Vec<CellValue<'static>>>);
+
+/// setup function called on the sheet before reading, e.g. with_skip_until
+type SetupFn = Box<dyn FnOnce(&mut XlsxSheet<'_>) -> Result<()> + Send>;
+
+fn join_error(e: tokio::task::JoinError) -> XlsxError {
+    XlsxError::Io(std::io::Error::other(e))
+}
+
+impl XlsxBook {
+    /// open xlsx book from an AsyncRead source, which is buffered into a temporary file (removed when the book dropped)
+    pub async fn from_async_read<R: AsyncRead + Unpin>(mut source: R, load_share: bool, options: ReadOptions) -> Result<XlsxBook> {
+        let temp_file = TempFile(temp_file_path(options.spill_dir.as_deref(), "xlsx"));
+        let mut file = tokio::fs::File::create(&temp_file.0).await?;
+        tokio::io::copy(&mut source, &mut file).await?;
+        file.flush().await?;
+        drop(file);
+        let path = temp_file.0.clone();
+        let mut book = tokio::task::spawn_blocking(move || XlsxBook::new_with_options(path, load_share, options)).await.map_err(join_error)??;
+        book.temp_file = Some(temp_file);
+        Ok(book)
+    }
+}
+
+/// args of XlsxBook::get_sheet_by_name
+struct SheetArgs {
+    sht_name: String,
+    iter_batch: usize,
+    skip_rows: u32,
+    left_ncol: ColNum,
+    right_ncol: ColNum,
+    first_row_is_header: bool,
+}
+
+enum StreamState {
+    Pending(Box<XlsxBook>, SheetArgs, Option<SetupFn>),
+    Running(Receiver<Result<Batch>>, JoinHandle<()>),
+    Done,
+}
+
+/// async stream of batches over a sheet.
+/// the xml is parsed on a blocking thread, and owned batches are forwarded through a bounded channel, so parsing pauses when the consumer is slow.
+/// the parsing stops when the stream dropped
+pub struct SheetStream {
+    state: StreamState,
+    capacity: usize,
+}
+
+impl SheetStream {
+    /// the args are the same as XlsxBook::get_sheet_by_name
+    pub fn new(book: XlsxBook, sht_name: &str, iter_batch: usize, skip_rows: u32, left_ncol: ColNum, right_ncol: ColNum, first_row_is_header: bool) -> Self {
+        let args = SheetArgs { sht_name: sht_name.to_string(), iter_batch, skip_rows, left_ncol, right_ncol, first_row_is_header };
+        Self { state: StreamState::Pending(Box::new(book), args, None), capacity: 2 }
+    }
+    /// max count of batches buffered in the channel (default: 2)
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = std::cmp::max(capacity, 1);
+        self
+    }
+    /// set up the sheet before reading, e.g. `|sheet| {sheet.with_skip_until(&checks); Ok(())}`
+    pub fn with_setup<F: FnOnce(&mut XlsxSheet<'_>) -> Result<()> + Send + 'static>(mut self, setup: F) -> Self {
+        if let StreamState::Pending(_, _, f) = &mut self.state {
+            *f = Some(Box::new(setup));
+        }
+        self
+    }
+    /// start parsing on a blocking thread
+    fn start(book: Box<XlsxBook>, args: SheetArgs, setup: Option<SetupFn>, capacity: usize) -> (Receiver<Result<Batch>>, JoinHandle<()>) {
+        let (tx, rx) = mpsc::channel(capacity);
+        let handle = tokio::task::spawn_blocking(move || {
+            let mut book = book;
+            let mut sheet = match book.get_sheet_by_name(&args.sht_name, args.iter_batch, args.skip_rows, args.left_ncol, args.right_ncol, args.first_row_is_header) {
+                Ok(sheet) => sheet,
+                Err(e) => {
+                    let _ = tx.blocking_send(Err(e));
+                    return;
+                }
+            };
+            if let Some(setup) = setup {
+                if let Err(e) = setup(&mut sheet) {
+                    let _ = tx.blocking_send(Err(e));
+                    return;
+                }
+            }
+            for batch in sheet {
+                let failed = batch.is_err();
+                let batch = batch.map(|(nums, rows)| (nums, rows.into_iter().map(|r| r.into_iter().map(CellValue::into_owned).collect()).collect()));
+                //  接收端关闭后停止解析
+                if tx.blocking_send(batch).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        (rx, handle)
+    }
+}
+
+impl Stream for SheetStream {
+    type Item = Result<Batch>;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let capacity = self.capacity;
+        if let StreamState::Pending(..) = self.state {
+            if let StreamState::Pending(book, args, setup) = std::mem::replace(&mut self.state, StreamState::Done) {
+                let (rx, handle) = Self::start(book, args, setup, capacity);
+                self.state = StreamState::Running(rx, handle);
+            }
+        }
+        let polled = match &mut self.state {
+            StreamState::Running(rx, handle) => match rx.poll_recv(cx) {
+                Poll::Ready(None) => match Pin::new(handle).poll(cx) {
+                    //  解析线程panic时返回错误
+                    Poll::Ready(Err(e)) => Poll::Ready(Some(Err(join_error(e)))),
+                    Poll::Ready(Ok(())) => Poll::Ready(None),
+                    Poll::Pending => Poll::Pending,
+                },
+                v => v,
+            },
+            _ => Poll::Ready(None),
+        };
+        if let Poll::Ready(None) | Poll::Ready(Some(Err(_))) = polled {
+            if matches!(self.state, StreamState::Running(..)) {
+                self.state = StreamState::Done;
+            }
+        }
+        polled
+    }
+}
@@ -1,9 +1,8 @@
 use std::collections::HashMap;
 
-use anyhow::{anyhow, Result};
-use rust_xlsxwriter::{Workbook, Worksheet, XlsxError, Format, IntoExcelData};
+use rust_xlsxwriter::{Workbook, Worksheet, Format, IntoExcelData};
 
-use crate::{CellValue, ColNum, RowNum};
+use crate::{error::{Result, XlsxError}, CellValue, ColNum, RowNum};
 
 impl IntoExcelData for CellValue<'_> {
     fn write<'a>(
@@ -11,7 +10,7 @@ impl IntoExcelData for CellValue<'_> {
         worksheet: &'a mut Worksheet,
         row: RowNum,
         col: ColNum,
-    ) -> Result<&'a mut Worksheet, XlsxError> {
+    ) -> std::result::Result<&'a mut Worksheet, rust_xlsxwriter::XlsxError> {
         match self {
             CellValue::Blank => {},
             CellValue::Error(_) => {},
@@ -46,7 +45,7 @@ impl IntoExcelData for CellValue<'_> {
         row: RowNum,
         col: ColNum,
         format: &'b Format,
-    ) -> Result<&'a mut Worksheet, XlsxError> {
+    ) -> std::result::Result<&'a mut Worksheet, rust_xlsxwriter::XlsxError> {
         match self {
             CellValue::Blank => {},
             CellValue::Error(_) => {},
@@ -141,7 +140,7 @@ impl XlsxWriter {
             }
             Ok((self.book.worksheet_from_name(shname)?, self.rows.get(shname).unwrap_or(&0).to_owned()))
         } else {
-            Err(anyhow!("cannot write saved workbook"))
+            Err(XlsxError::InvalidState("cannot write saved workbook".to_string()))
         }
     }
     /// append one row to sheet   
@@ -177,7 +176,7 @@ impl XlsxWriter {
         let (sheet, mut irow) = self.get_sheet_mut(shname)?;
         // 若nrows的长度为0，则不写行号
         if nrows.len() > 0 && nrows.len() != data.len() {
-            return Err(anyhow!("the length of nrows is not equal to the length of data".to_string()));
+            return Err(XlsxError::InvalidState("the length of nrows is not equal to the length of data".to_string()));
         }
 
         let mut icol;
@@ -209,14 +208,14 @@ impl XlsxWriter {
                 if let Some(colnum) = columns.get(&colname) {
                     sheet.write(irow, *colnum, colval)?;
                 } else {
-                    return Err(anyhow!("column name {} not found", colname));
+                    return Err(XlsxError::ColumnNotFound(colname));
                 }
             }
             irow += 1;
             self.rows.insert(shname.to_owned(), irow);
             Ok(())
         } else {
-            Err(anyhow!("columns not set"))
+            Err(XlsxError::InvalidState(format!("columns of sheet {} not set", shname)))
         }
     }
     
@@ -232,7 +231,7 @@ impl XlsxWriter {
                     if let Some(colnum) = columns.get(&colname) {
                         sheet.write(irow, *colnum, colval)?;
                     } else {
-                        return Err(anyhow!("column name {} not found", colname));
+                        return Err(XlsxError::ColumnNotFound(colname));
                     }
                 }
                 irow += 1;
@@ -240,7 +239,7 @@ impl XlsxWriter {
             self.rows.insert(shname.to_owned(), irow);
             Ok(())
         } else {
-            Err(anyhow!("columns not set"))
+            Err(XlsxError::InvalidState(format!("columns of sheet {} not set", shname)))
         }
     }
     /// save as xlsx file, can only run once each writer
@@ -250,7 +249,7 @@ impl XlsxWriter {
             self.open = false;
             Ok(())
         } else {
-            Err(anyhow!("cannot save saved workbook"))
+            Err(XlsxError::InvalidState("cannot save saved workbook".to_string()))
         }
     }
 }
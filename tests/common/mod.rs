@@ -0,0 +1,39 @@
+// 集成测试和性能测试共用的测试文件构造
+#![allow(dead_code)]
+use std::{io::Write, path::{Path, PathBuf}};
+use xlsx_batch_reader::zip::{write::SimpleFileOptions, ZipWriter};
+
+/// build a minimal xlsx file with one sheet named Sheet1
+pub fn make_xlsx(name: &str, sheet_xml: &str) -> PathBuf {
+    make_xlsx_with(name, sheet_xml, &[])
+}
+
+/// build a minimal xlsx file with one sheet named Sheet1 and extra parts, which replace the default parts with the same name
+pub fn make_xlsx_with(name: &str, sheet_xml: &str, extra: &[(&str, &str)]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("xlsx_batch_reader_{}_{}.xlsx", name, std::process::id()));
+    let mut zip = ZipWriter::new(std::fs::File::create(&path).unwrap());
+    let parts = [
+        ("xl/_rels/workbook.xml.rels", r#"<?xml version="1.0" encoding="UTF-8"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#),
+        ("xl/workbook.xml", r#"<?xml version="1.0" encoding="UTF-8"?><workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="Sheet1" sheetId="1" r:id="rId1"/></sheets></workbook>"#),
+        ("xl/worksheets/sheet1.xml", sheet_xml),
+    ];
+    for (part, xml) in parts.iter().filter(|(p, _)| !extra.iter().any(|(e, _)| e == p)).chain(extra) {
+        zip.start_file(part, SimpleFileOptions::default()).unwrap();
+        zip.write_all(xml.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+    path
+}
+
+/// append a binary part (e.g. xl/vbaProject.bin) to the xlsx file
+pub fn append_part(path: &Path, part: &str, data: &[u8]) {
+    let mut zip = ZipWriter::new_append(std::fs::OpenOptions::new().read(true).write(true).open(path).unwrap()).unwrap();
+    zip.start_file(part, SimpleFileOptions::default()).unwrap();
+    zip.write_all(data).unwrap();
+    zip.finish().unwrap();
+}
+
+/// inline string cell
+pub fn istr(addr: &str, val: &str) -> String {
+    format!(r#"<c r="{addr}" t="inlineStr"><is><t>{val}</t></is></c>"#)
+}
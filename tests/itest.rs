@@ -1,5 +1,13 @@
 // 集成测试
-use xlsx_batch_reader::{get_num_from_ord, get_ord_from_num, get_tuple_from_ord};
+mod common;
+use common::{istr, make_xlsx, make_xlsx_with};
+#[cfg(feature = "vba")]
+use std::io::Write;
+#[cfg(feature = "vba")]
+use common::append_part;
+#[cfg(feature = "vba")]
+use xlsx_batch_reader::read::VbaModuleKind;
+use xlsx_batch_reader::{get_num_from_ord, get_ord_from_num, get_tuple_from_ord, is_merged_cell, read::{resolve_external_refs, AnchorKind, CancelToken, CfRuleKind, PropertyValue, ReadLimits, ReadOptions, ValidationKind, XlsxBook}, CellValue, MergedRanges, XlsxError, MAX_COL_NUM};
 
 #[test]
 pub fn test_ord_to_num(){
@@ -16,3 +24,894 @@ pub fn test_ord_to_num(){
     assert!(get_tuple_from_ord("A1".as_bytes()).unwrap() == (1, 1));
     assert!(get_tuple_from_ord("B3".as_bytes()).unwrap() == (3, 2));
 }
+
+#[test]
+pub fn test_merged_ranges_lookup(){
+    let rngs = vec![((1, 1), (2, 2)), ((1, 4), (1, 6)), ((3, 2), (5, 2)), ((10, 1), (10, 3))];
+    let mgs = MergedRanges::new(rngs.clone());
+    for row in 0..12 {
+        for col in 0..8 {
+            assert_eq!(mgs.is_merged_cell(row, col), is_merged_cell(&rngs, row, col));
+        }
+    }
+    assert_eq!(mgs.lookup(4, 2), Some((&((3, 2), (5, 2)), false, (3, 1))));
+    assert_eq!(mgs.lookup(1, 4).map(|v| v.1), Some(true));
+    assert_eq!(mgs.ranges_in_row(1).count(), 2);
+    //  高合并区域与大量小合并区域交错
+    let mut rngs = vec![((1, 1), (300, 1)), ((2, 8), (299, 9))];
+    for r in 1..=300 {
+        rngs.push(((r, 3), (r, 4)));
+        if r % 3 == 1 {
+            rngs.push(((r, 6), (r + 2, 6)));
+        }
+    }
+    let mgs = MergedRanges::new(rngs.clone());
+    for row in 0..303 {
+        for col in 0..11 {
+            assert_eq!(mgs.is_merged_cell(row, col), is_merged_cell(&rngs, row, col));
+        }
+    }
+    assert_eq!(mgs.lookup(150, 9), Some((&((2, 8), (299, 9)), false, (298, 2))));
+    assert_eq!(mgs.ranges_in_row(5).count(), 4);
+    assert_eq!(mgs.ranges_in_row(300).count(), 3);
+    assert_eq!(MergedRanges::new(Vec::new()).lookup(1, 1), None);
+}
+
+#[test]
+pub fn test_multi_header_rows(){
+    let sheet_xml = format!(
+        r#"<worksheet><dimension ref="A1:E3"/><sheetData><row r="1">{}{}{}</row><row r="2">{}{}{}{}</row><row r="3"><c r="A3"><v>1</v></c><c r="B3"><v>2</v></c></row></sheetData><mergeCells count="3"><mergeCell ref="A1:A2"/><mergeCell ref="B1:C1"/><mergeCell ref="D1:E1"/></mergeCells></worksheet>"#,
+        istr("A1", "ID"), istr("B1", "Q1"), istr("D1", "Q2"),
+        istr("B2", "Revenue"), istr("C2", "Cost"), istr("D2", "Revenue"), istr("E2", "Cost"),
+    );
+    let path = make_xlsx("multi_header", &sheet_xml);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, MAX_COL_NUM, false).unwrap();
+    sheet.with_header_rows(2).unwrap();
+    assert_eq!(sheet.get_header_names().unwrap(), vec!["ID", "Q1.Revenue", "Q1.Cost", "Q2.Revenue", "Q2.Cost"]);
+    assert_eq!(sheet.get_header_row().unwrap().0, 2);
+    assert_eq!(sheet.get_column_by_name("Q2.Cost").unwrap(), Some(5));
+    let (rows, _) = sheet.get_remaining_cells().unwrap().unwrap();
+    assert_eq!(rows, vec![3]);
+    }
+    //  没有合并区域时，空白单元格不填充
+    let plain = make_xlsx("multi_header_plain", &sheet_xml.replace(r#"<mergeCells count="3"><mergeCell ref="A1:A2"/><mergeCell ref="B1:C1"/><mergeCell ref="D1:E1"/></mergeCells>"#, ""));
+    {
+    let mut book = XlsxBook::new(&plain, true).unwrap();
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, MAX_COL_NUM, false).unwrap();
+    sheet.with_header_rows(2).unwrap();
+    assert_eq!(sheet.get_header_names().unwrap(), vec!["ID", "Q1.Revenue", "Cost", "Q2.Revenue", "Cost"]);
+    }
+    std::fs::remove_file(plain).unwrap();
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_detect_header_row(){
+    let sheet_xml = format!(
+        r#"<worksheet><sheetData><row r="1">{}</row><row r="2">{}</row><row r="4">{}{}{}</row><row r="5">{}<c r="B5"><v>3</v></c><c r="C5"><v>1.5</v></c></row><row r="6">{}<c r="B6"><v>4</v></c><c r="C6"><v>2.5</v></c></row></sheetData></worksheet>"#,
+        istr("A1", "Sales Report"), istr("A2", "unit: k"),
+        istr("A4", "Name"), istr("B4", "Qty"), istr("C4", "Price"),
+        istr("A5", "apple"), istr("A6", "pear"),
+    );
+    let path = make_xlsx("detect_header", &sheet_xml);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, MAX_COL_NUM, false).unwrap();
+    let (row, confidence) = sheet.detect_header_row(10).unwrap().unwrap();
+    assert_eq!(row, 4);
+    assert!(confidence > 0.5);
+    assert_eq!(sheet.get_header_names().unwrap(), vec!["Name", "Qty", "Price"]);
+    let title: String = sheet.get_captured_vals().unwrap()["A1"].get().unwrap().unwrap();
+    assert_eq!(title, "Sales Report");
+    let (rows, _) = sheet.get_remaining_cells().unwrap().unwrap();
+    assert_eq!(rows, vec![5, 6]);
+    }
+    //  未扫描到行时保留原有的标题行和captures设置
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, MAX_COL_NUM, true).unwrap();
+    sheet.with_capture_vals(["A1".to_string()].into());
+    assert!(sheet.detect_header_row(0).unwrap().is_none());
+    let title: String = sheet.get_captured_vals().unwrap()["A1"].get().unwrap().unwrap();
+    assert_eq!(title, "Sales Report");
+    assert_eq!(sheet.get_header_names().unwrap(), vec!["Sales Report"]);
+    }
+    let empty = make_xlsx("detect_header_empty", "<worksheet><sheetData/></worksheet>");
+    {
+    let mut book = XlsxBook::new(&empty, true).unwrap();
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, MAX_COL_NUM, false).unwrap();
+    sheet.with_capture_vals(["A1".to_string()].into());
+    assert!(sheet.detect_header_row(10).unwrap().is_none());
+    assert!(matches!(sheet.get_captured_vals(), Err(XlsxError::InvalidState(_))));
+    assert!(sheet.get_remaining_cells().unwrap().is_none());
+    }
+    std::fs::remove_file(empty).unwrap();
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_fill_merged(){
+    let sheet_xml = format!(
+        r#"<worksheet><sheetData><row r="1">{}{}</row><row r="2">{}<c r="B2"/><c r="C2"><v>1</v></c></row><row r="3"><c r="A3"/><c r="C3"><v>2</v></c></row></sheetData><mergeCells count="2"><mergeCell ref="A2:A3"/><mergeCell ref="A1:B1"/></mergeCells></worksheet>"#,
+        istr("A1", "group"), istr("C1", "x"), istr("A2", "a"),
+    );
+    let path = make_xlsx("fill_merged", &sheet_xml);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, 3, false).unwrap();
+    sheet.with_fill_merged(true).unwrap();
+    let (rows, data) = sheet.get_remaining_cells().unwrap().unwrap();
+    assert_eq!(rows, vec![1, 2, 3]);
+    let vals: Vec<Vec<String>> = data.iter().map(|r| r.iter().map(|c| c.get::<String>().unwrap().unwrap()).collect()).collect();
+    assert_eq!(vals, vec![vec!["group", "group", "x"], vec!["a", "", "1"], vec!["a", "", "2"]]);
+    assert_eq!(sheet.get_merged_ranges().unwrap().len(), 2);
+    }
+    #[cfg(feature = "cached")]
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let sheet = book.get_cached_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, 3, false).unwrap().with_fill_merged(true).unwrap();
+    let a3: String = sheet.get_cell_value("A3").unwrap().get().unwrap().unwrap();
+    assert_eq!(a3, "a");
+    //  左上角在缓存范围之外，需在缓存前读取
+    let cached = book.get_cached_sheet_by_name(&"Sheet1".to_string(), 100, 0, 2, 3, false).unwrap();
+    assert!(matches!(cached.with_fill_merged(true), Err(XlsxError::InvalidState(_))));
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 2, 3, false).unwrap();
+    sheet.with_fill_merged(true).unwrap();
+    let sheet = sheet.into_cached_sheet().unwrap().with_fill_merged(true).unwrap();
+    let b1: String = sheet.get_all_cells()[&1][0].get().unwrap().unwrap();
+    assert_eq!(b1, "group");
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 2, 1, 3, false).unwrap();
+    sheet.with_fill_merged(true).unwrap();
+    let sheet = sheet.into_cached_sheet().unwrap();
+    let a3: String = sheet.get_all_cells()[&3][0].get().unwrap().unwrap();
+    assert_eq!(a3, "a");
+    //  合并区域不在缓存范围内时无需预先读取
+    let sheet = book.get_cached_sheet_by_name(&"Sheet1".to_string(), 100, 3, 3, 3, false).unwrap().with_fill_merged(true).unwrap();
+    assert_eq!(sheet.row_range().0, 4);
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_prescan(){
+    let sheet_xml = format!(
+        r#"<worksheet><sheetData><row r="1">{}{}</row></sheetData><autoFilter ref="A1:B1"/><mergeCells count="1"><mergeCell ref="A1:A2"/></mergeCells><hyperlinks><hyperlink ref="B1" r:id="rId1"/><hyperlink ref="A1" location="Sheet1!A2" display="go"/></hyperlinks></worksheet>"#,
+        istr("A1", "a"), istr("B1", "b"),
+    );
+    let rels = r#"<Relationships><Relationship Id="rId1" Type="hyperlink" Target="https://example.com" TargetMode="External"/></Relationships>"#;
+    let path = make_xlsx_with("prescan", &sheet_xml, &[("xl/worksheets/_rels/sheet1.xml.rels", rels)]);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, MAX_COL_NUM, false).unwrap();
+    let tail = sheet.with_prescan().unwrap();
+    assert_eq!(tail.auto_filter.as_deref(), Some("A1:B1"));
+    assert_eq!(tail.hyperlinks[0].target.as_deref(), Some("https://example.com"));
+    assert_eq!(tail.hyperlinks[1].location.as_deref(), Some("Sheet1!A2"));
+    assert_eq!(sheet.get_merged_ranges().unwrap().to_vec(), vec![((1, 1), (2, 1))]);
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_error_kinds(){
+    let sheet_xml = format!(r#"<worksheet><sheetData><row r="1">{}<c r="B1"><v>x1</v></c></row></sheetData></worksheet>"#, istr("A1", "abc"));
+    let path = make_xlsx("error_kinds", &sheet_xml);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    assert!(matches!(book.get_sheet_by_name(&"Nope".to_string(), 100, 0, 1, MAX_COL_NUM, false), Err(XlsxError::SheetNotFound(_))));
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, MAX_COL_NUM, false).unwrap();
+    match sheet.get_remaining_cells() {
+        Err(XlsxError::Conversion { sheet, row, col, target, .. }) => {
+            assert_eq!((sheet.as_deref(), row, col, target), (Some("Sheet1"), Some(1), Some(2), "f64"));
+        },
+        _ => panic!("conversion error expected")
+    }
+    }
+    assert!(matches!(get_tuple_from_ord("A".as_bytes()), Err(XlsxError::InvalidCellRef(_))));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_lenient_mode(){
+    let sheet_xml = r#"<worksheet><sheetData><row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1" s="7"><v>1</v></c><c r="C1"><v>bad</v></c></row></sheetData><mergeCells count="2"><mergeCell ref="A1:B1"/></mergeCells></worksheet>"#;
+    let sst = r#"<sst count="2" uniqueCount="2"><si><t>a</t></si></sst>"#;
+    let path = make_xlsx_with("lenient", sheet_xml, &[("xl/sharedStrings.xml", sst)]);
+    assert!(XlsxBook::new(&path, true).is_err());
+    {
+    let mut book = XlsxBook::new_with_options(&path, true, ReadOptions { strict: false, ..Default::default() }).unwrap();
+    assert_eq!(book.get_warnings().len(), 1);
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, MAX_COL_NUM, false).unwrap();
+    let (_, data) = sheet.get_remaining_cells().unwrap().unwrap();
+    assert!(matches!(data[0][2], CellValue::Error(_)));
+    assert_eq!(sheet.get_merged_ranges().unwrap().len(), 1);
+    assert_eq!(sheet.get_warnings().len(), 3);
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_read_limits(){
+    let sheet_xml = format!(r#"<worksheet><sheetData><row r="1">{}</row><row r="2">{}</row><row r="3">{}</row></sheetData></worksheet>"#, istr("A1", "a"), istr("A2", &"x".repeat(100)), istr("C3", "c"));
+    let path = make_xlsx("limits", &sheet_xml);
+    let read_with = |limits: ReadLimits| -> Result<(), XlsxError> {
+        let mut book = XlsxBook::new_with_options(&path, true, ReadOptions { limits, ..Default::default() })?;
+        let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, MAX_COL_NUM, false)?;
+        sheet.get_remaining_cells()?;
+        Ok(())
+    };
+    assert!(read_with(ReadLimits::default()).is_ok());
+    assert!(matches!(read_with(ReadLimits { max_rows: Some(2), ..Default::default() }), Err(XlsxError::LimitExceeded(_))));
+    assert!(matches!(read_with(ReadLimits { max_cols: Some(2), ..Default::default() }), Err(XlsxError::LimitExceeded(_))));
+    assert!(matches!(read_with(ReadLimits { max_cell_text: Some(50), ..Default::default() }), Err(XlsxError::LimitExceeded(_))));
+    assert!(matches!(read_with(ReadLimits { max_part_size: Some(100), ..Default::default() }), Err(XlsxError::LimitExceeded(_))));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_spill_shared_strings(){
+    let sheet_xml = r#"<worksheet><sheetData><row r="1"><c r="A1" t="s"><v>1</v></c><c r="B1" t="s"><v>0</v></c><c r="C1" t="s"><v>2</v></c></row></sheetData></worksheet>"#;
+    let sst = r#"<sst count="3" uniqueCount="3"><si><t>甲</t></si><si><t>b</t></si><si><r><t>c</t></r><r><t>d</t></r></si></sst>"#;
+    let path = make_xlsx_with("spill", sheet_xml, &[("xl/sharedStrings.xml", sst)]);
+    let dir = std::env::temp_dir().join("xlsx_batch_reader_spill");
+    std::fs::create_dir_all(&dir).unwrap();
+    {
+    let options = ReadOptions { shared_strings_spill: Some(2), spill_dir: Some(dir.clone()), ..Default::default() };
+    let mut book = XlsxBook::new_with_options(&path, true, options).unwrap();
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, MAX_COL_NUM, false).unwrap();
+    let (_, data) = sheet.get_remaining_cells().unwrap().unwrap();
+    let vals: Vec<String> = data[0].iter().map(|v| v.get::<String>().unwrap().unwrap()).collect();
+    assert_eq!(vals, vec!["b", "甲", "cd"]);
+    assert!(data[0].iter().all(|v| matches!(v, CellValue::String(_))));
+    }
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    //  字符串跨越多个块，超出缓存的块数
+    let n = 5000;
+    let sst: String = (0..n).map(|i| format!("<si><t>s{i}</t></si>")).collect();
+    let inxs: Vec<usize> = (0..300).map(|i| i * 37 % n).chain([0, n - 1]).collect();
+    let cells: String = inxs.iter().enumerate().map(|(j, i)| format!(r#"<c r="{}1" t="s"><v>{i}</v></c>"#, get_ord_from_num(j as u16 + 1).unwrap())).collect();
+    let many = make_xlsx_with("spill_blocks", &format!(r#"<worksheet><sheetData><row r="1">{cells}</row></sheetData></worksheet>"#), &[("xl/sharedStrings.xml", &format!(r#"<sst uniqueCount="{n}">{sst}</sst>"#))]);
+    {
+    let options = ReadOptions { shared_strings_spill: Some(2), ..Default::default() };
+    let mut book = XlsxBook::new_with_options(&many, true, options).unwrap();
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, MAX_COL_NUM, false).unwrap();
+    let (_, data) = sheet.get_remaining_cells().unwrap().unwrap();
+    let vals: Vec<String> = data[0].iter().map(|v| v.get::<String>().unwrap().unwrap()).collect();
+    assert_eq!(vals, inxs.iter().map(|i| format!("s{i}")).collect::<Vec<_>>());
+    }
+    std::fs::remove_file(many).unwrap();
+    std::fs::remove_dir(dir).unwrap();
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_resume_from_checkpoint(){
+    let mut rows = format!(r#"<row r="1">{}</row><row r="2">{}{}</row>"#, istr("A1", "title"), istr("A2", "id"), istr("B2", "name"));
+    for r in 3..=7 {
+        rows += &format!(r#"<row r="{r}"><c r="A{r}"><v>{r}</v></c>{}</row>"#, istr(&format!("B{r}"), "x"));
+    }
+    let path = make_xlsx("resume", &format!("<worksheet><sheetData>{rows}</sheetData></worksheet>"));
+    let checkpoint = {
+        let mut book = XlsxBook::new(&path, true).unwrap();
+        let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 2, 1, 1, MAX_COL_NUM, true).unwrap();
+        sheet.with_capture_vals(["B2".to_string()].into());
+        let (nums, _) = sheet.next().unwrap().unwrap();
+        assert_eq!(nums, vec![3, 4]);
+        sheet.checkpoint()
+    };
+    assert_eq!(checkpoint.row, 4);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let mut sheet = book.resume_sheet(&checkpoint, 2).unwrap();
+    assert_eq!(sheet.get_header_names().unwrap(), vec!["id", "name"]);
+    let name: String = sheet.get_captured_vals().unwrap()["B2"].get().unwrap().unwrap();
+    assert_eq!(name, "name");
+    let (nums, _) = sheet.get_remaining_cells().unwrap().unwrap();
+    assert_eq!(nums, vec![5, 6, 7]);
+    }
+    //  恢复后重新设置的skip_until和read_before保持检查点的匹配状态
+    let checkpoint = {
+        let mut book = XlsxBook::new(&path, true).unwrap();
+        let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 2, 0, 1, MAX_COL_NUM, false).unwrap();
+        sheet.with_skip_until(&[("A".to_string(), "id".to_string())].into());
+        let (nums, _) = sheet.next().unwrap().unwrap();
+        assert_eq!(nums, vec![2, 3]);
+        sheet.checkpoint()
+    };
+    assert!(checkpoint.skip_until_matched);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let mut sheet = book.resume_sheet(&checkpoint, 2).unwrap();
+    sheet.with_skip_until(&[("A".to_string(), "id".to_string())].into());
+    let (nums, _) = sheet.get_remaining_cells().unwrap().unwrap();
+    assert_eq!(nums, vec![4, 5, 6, 7]);
+    }
+    let checkpoint = {
+        let mut book = XlsxBook::new(&path, true).unwrap();
+        let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 2, 0, 1, MAX_COL_NUM, false).unwrap();
+        sheet.with_read_before(&[("A".to_string(), "5".to_string())].into());
+        let (nums, _) = sheet.get_remaining_cells().unwrap().unwrap();
+        assert_eq!(nums, vec![1, 2, 3, 4]);
+        sheet.checkpoint()
+    };
+    assert!(checkpoint.read_before_matched);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let mut sheet = book.resume_sheet(&checkpoint, 2).unwrap();
+    assert!(sheet.get_remaining_cells().unwrap().is_none());
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_progress_and_cancel(){
+    let rows: String = (1..=10).map(|r| format!(r#"<row r="{r}"><c r="A{r}"><v>{r}</v></c></row>"#)).collect();
+    let path = make_xlsx("progress", &format!(r#"<worksheet><dimension ref="A1:A10"/><sheetData>{rows}</sheetData></worksheet>"#));
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let reports = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, MAX_COL_NUM, false).unwrap();
+    let cloned = reports.clone();
+    sheet.with_progress(4, move |p| cloned.lock().unwrap().push((p.rows, p.total_rows, p.fraction())));
+    sheet.get_remaining_cells().unwrap();
+    drop(sheet);
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.iter().map(|v| v.0).collect::<Vec<_>>(), vec![4, 8, 10]);
+    assert_eq!(reports[0].1, Some(10));
+    assert_eq!(reports[2].2, 1.0);
+    drop(reports);
+
+    let token = CancelToken::new();
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 3, 0, 1, MAX_COL_NUM, false).unwrap();
+    sheet.with_cancel_token(token.clone());
+    assert!(sheet.next().unwrap().is_ok());
+    token.cancel();
+    assert!(matches!(sheet.next(), Some(Err(XlsxError::Cancelled))));
+    assert!(sheet.next().is_none());
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_sheet_stream(){
+    use std::pin::Pin;
+    use xlsx_batch_reader::stream::SheetStream;
+    use xlsx_batch_reader::futures_core::Stream;
+    let rows: String = (1..=5).map(|r| format!(r#"<row r="{r}"><c r="A{r}"><v>{r}</v></c></row>"#)).collect();
+    let path = make_xlsx("stream", &format!("<worksheet><sheetData>{rows}</sheetData></worksheet>"));
+    let source = tokio::fs::File::open(&path).await.unwrap();
+    let book = XlsxBook::from_async_read(source, true, ReadOptions::default()).await.unwrap();
+    let mut stream = SheetStream::new(book, "Sheet1", 2, 0, 1, MAX_COL_NUM, true).with_capacity(1);
+    let mut nums = Vec::new();
+    while let Some(batch) = std::future::poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+        let (rows, data) = batch.unwrap();
+        assert!(rows.len() <= 2 && matches!(data[0][0], CellValue::Number(_)));
+        nums.extend(rows);
+    }
+    assert_eq!(nums, vec![2, 3, 4, 5]);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_for_each_row(){
+    let sheet_xml = format!(r#"<worksheet><sheetData><row r="1">{}{}</row><row r="2"><c r="A2"><v>1.5</v></c></row><row r="3"><c r="B3" t="b"><v>1</v></c></row></sheetData></worksheet>"#, istr("A1", "a"), istr("B1", "b"));
+    let path = make_xlsx("for_each_row", &sheet_xml);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, 2, true).unwrap();
+    let mut rows = Vec::new();
+    sheet.for_each_row(|r, cells| {
+        rows.push((r, format!("{:?}", cells)));
+        Ok(())
+    }).unwrap();
+    assert_eq!(rows, vec![(2, "[Number(1.5), Blank]".to_string()), (3, "[Blank, Bool(true)]".to_string())]);
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_parallel_parsing(){
+    let mut rows = format!(r#"<row r="1">{}</row><row r="2">{}{}{}</row>"#, istr("A1", "title"), istr("A2", "id"), istr("B2", "name"), istr("C2", "score"));
+    for r in 3..=3000 {
+        let name = if r == 2900 {"end".to_string()} else {format!("n{r}")};
+        rows += &format!(r#"<row r="{r}" spans="1:3"><c r="A{r}"><v>{r}</v></c>{}<c r="C{r}"><v>{}.5</v></c></row>"#, istr(&format!("B{r}"), &name), r % 7);
+    }
+    let path = make_xlsx("parallel", &format!(r#"<worksheet><dimension ref="A1:C3000"/><sheetData>{rows}</sheetData><mergeCells count="1"><mergeCell ref="A1:C1"/></mergeCells></worksheet>"#));
+    let read = |threads: usize| {
+        let mut book = XlsxBook::new(&path, true).unwrap();
+        let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 500, 0, 1, 3, true).unwrap();
+        if threads > 0 {
+            sheet.with_parallel(threads).unwrap();
+        }
+        sheet.with_skip_until(&[("A".to_string(), "id".to_string())].into());
+        sheet.with_read_before(&[("B".to_string(), "end".to_string())].into());
+        sheet.with_capture_vals(["B2".to_string()].into());
+        let header = format!("{:?}", sheet.get_header_row().unwrap());
+        let captured = format!("{:?}", sheet.get_captured_vals().unwrap());
+        let mut data = Vec::new();
+        for batch in &mut sheet {
+            let (nums, rows) = batch.unwrap();
+            data.extend(nums.into_iter().zip(rows.into_iter().map(|r| format!("{:?}", r))));
+        }
+        let merged = sheet.get_merged_ranges().unwrap().to_vec();
+        (header, captured, data, merged)
+    };
+    let expected = read(0);
+    assert_eq!(expected.2.len(), 2897);
+    assert_eq!(read(4), expected);
+    assert_eq!(read(1), expected);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_parallel_waves(){
+    //  解压后超过8MB，单线程分3批、双线程分2批解析，批次间的未完整行需拼接到下一批
+    let mut rows = String::with_capacity(10 << 20);
+    for r in 1..=90000 {
+        let name = match r {
+            40000 => "start".to_string(),
+            85000 => "end".to_string(),
+            _ => format!("name_{r:0>40}"),
+        };
+        rows += &format!(r#"<row r="{r}"><c r="A{r}"><v>{r}</v></c>{}<c r="C{r}"><v>{}.25</v></c></row>"#, istr(&format!("B{r}"), &name), r % 9);
+    }
+    let path = make_xlsx("parallel_waves", &format!("<worksheet><sheetData>{rows}</sheetData></worksheet>"));
+    let read = |threads: usize| {
+        let mut book = XlsxBook::new(&path, true).unwrap();
+        let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 5000, 0, 1, 3, false).unwrap();
+        if threads > 0 {
+            sheet.with_parallel(threads).unwrap();
+        }
+        sheet.with_skip_until(&[("B".to_string(), "start".to_string())].into());
+        sheet.with_read_before(&[("B".to_string(), "end".to_string())].into());
+        let mut data = Vec::new();
+        for batch in &mut sheet {
+            let (nums, rows) = batch.unwrap();
+            data.extend(nums.into_iter().zip(rows.into_iter().map(|r| format!("{:?}", r))));
+        }
+        data
+    };
+    let expected = read(0);
+    assert_eq!(expected.len(), 45000);
+    assert_eq!(expected[0].0, 40000);
+    assert_eq!(read(1), expected);
+    assert_eq!(read(2), expected);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_book_properties(){
+    let core = r#"<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"><dc:title>Q1 &amp; Q2</dc:title><dc:creator>alice</dc:creator><cp:lastModifiedBy>bob</cp:lastModifiedBy><dcterms:created xsi:type="dcterms:W3CDTF">2024-01-02T03:04:05Z</dcterms:created></cp:coreProperties>"#;
+    let app = r#"<Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/extended-properties"><Application>Microsoft Excel</Application><Company>ACME</Company></Properties>"#;
+    let custom = r#"<Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/custom-properties" xmlns:vt="http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes"><property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="2" name="Dept"><vt:lpwstr>Finance</vt:lpwstr></property><property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="3" name="Version"><vt:i4>3</vt:i4></property><property fmtid="{D5CDD505-2E9C-101B-9397-08002B2CF9AE}" pid="4" name="Final"><vt:bool>true</vt:bool></property></Properties>"#;
+    let path = make_xlsx_with("props", "<worksheet><sheetData/></worksheet>", &[("docProps/core.xml", core), ("docProps/app.xml", app), ("docProps/custom.xml", custom)]);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let props = book.get_properties().unwrap();
+    assert_eq!(props.title.as_deref(), Some("Q1 & Q2"));
+    assert_eq!(props.creator.as_deref(), Some("alice"));
+    assert_eq!(props.last_modified_by.as_deref(), Some("bob"));
+    assert_eq!(props.created.unwrap().to_string(), "2024-01-02 03:04:05");
+    assert!(props.modified.is_none());
+    assert_eq!(props.company.as_deref(), Some("ACME"));
+    assert_eq!(props.custom["Dept"], PropertyValue::String("Finance".to_string()));
+    assert_eq!(props.custom["Version"], PropertyValue::Int(3));
+    assert_eq!(props.custom["Final"], PropertyValue::Bool(true));
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_data_validation(){
+    let sheet_xml = format!(
+        r#"<worksheet><sheetData><row r="1">{}{}{}</row><row r="2">{}<c r="B2"><v>5</v></c>{}</row><row r="3">{}<c r="B3"><v>11</v></c></row></sheetData><dataValidations count="2"><dataValidation type="list" allowBlank="1" sqref="A2:A3"><formula1>"Yes,No"</formula1></dataValidation><dataValidation type="whole" operator="between" sqref="B2 B3"><formula1>1</formula1><formula2>10</formula2></dataValidation></dataValidations><extLst><ext uri="{{CCE6A557-97BC-4b89-ADB6-D9C93CAAB3DF}}"><x14:dataValidations count="1"><x14:dataValidation type="textLength" operator="lessThanOrEqual"><x14:formula1><xm:f>3</xm:f></x14:formula1><xm:sqref>C2</xm:sqref></x14:dataValidation></x14:dataValidations></ext></extLst></worksheet>"#,
+        istr("A1", "flag"), istr("B1", "num"), istr("C1", "code"), istr("A2", "Yes"), istr("C2", "abcd"), istr("A3", "Maybe"),
+    );
+    let path = make_xlsx("validation", &sheet_xml);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, MAX_COL_NUM, true).unwrap();
+    let rules = sheet.get_data_validations().unwrap();
+    assert_eq!(rules.len(), 3);
+    assert_eq!(rules[0].list_values, Some(vec!["Yes".to_string(), "No".to_string()]));
+    assert_eq!(rules[1].ranges, vec![((2, 2), (2, 2)), ((3, 2), (3, 2))]);
+    assert_eq!((rules[2].kind, rules[2].sqref.as_str()), (ValidationKind::TextLength, "C2"));
+    sheet.with_validation(true).unwrap();
+    sheet.get_remaining_cells().unwrap();
+    let found: Vec<_> = sheet.get_violations().iter().map(|v| (v.row, v.col)).collect();
+    assert_eq!(found, vec![(2, 3), (3, 1), (3, 2)]);
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_conditional_formatting(){
+    let sheet_xml = r#"<worksheet><sheetData><row r="1"><c r="A1"><v>150</v></c><c r="B1"><v>20</v></c><c r="C1"><v>300</v></c></row></sheetData><conditionalFormatting sqref="A1:B1"><cfRule type="cellIs" dxfId="0" priority="2" operator="greaterThan"><formula>100</formula></cfRule><cfRule type="expression" dxfId="1" priority="1"><formula>$A1&gt;$B1</formula></cfRule></conditionalFormatting><conditionalFormatting sqref="C1:C5"><cfRule type="colorScale" priority="3"><colorScale><cfvo type="min"/><cfvo type="max"/><color rgb="FFF8696B"/><color rgb="FF63BE7B"/></colorScale></cfRule><cfRule type="top10" priority="4" rank="3" bottom="1" percent="1"/></conditionalFormatting></worksheet>"#;
+    let styles = r#"<styleSheet><cellXfs count="1"><xf numFmtId="0"/></cellXfs><dxfs count="2"><dxf><font><b/><color rgb="FF9C0006"/></font><fill><patternFill><bgColor rgb="FFFFC7CE"/></patternFill></fill></dxf><dxf><numFmt numFmtId="164" formatCode="0.00%"/></dxf></dxfs></styleSheet>"#;
+    let path = make_xlsx_with("cond_format", sheet_xml, &[("xl/styles.xml", styles)]);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    assert_eq!(book.get_dxf_styles().len(), 2);
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, MAX_COL_NUM, false).unwrap();
+    let cfs = sheet.get_conditional_formats().unwrap().clone();
+    assert_eq!(cfs.len(), 2);
+    let cell_is = &cfs[0].rules[0];
+    let dxf = cell_is.dxf.as_ref().unwrap();
+    assert_eq!((dxf.bold, dxf.font_color.as_deref(), dxf.fill_color.as_deref()), (true, Some("FF9C0006"), Some("FFFFC7CE")));
+    assert_eq!(cfs[0].rules[1].formulas, vec!["$A1>$B1".to_string()]);
+    assert_eq!(cfs[0].rules[1].dxf.as_ref().unwrap().num_fmt.as_deref(), Some("0.00%"));
+    assert_eq!(cfs[1].rules[0].colors, vec!["FFF8696B".to_string(), "FF63BE7B".to_string()]);
+    assert_eq!((&cfs[1].rules[1].kind, cfs[1].rules[1].rank, cfs[1].rules[1].bottom), (&CfRuleKind::Top10, Some(3), true));
+    let (_, data) = sheet.get_remaining_cells().unwrap().unwrap();
+    assert!(cfs[0].contains(1, 2) && !cfs[0].contains(2, 1));
+    assert_eq!((cell_is.evaluate(&data[0][0]), cell_is.evaluate(&data[0][1])), (Some(true), Some(false)));
+    assert_eq!(cfs[0].rules[1].evaluate(&data[0][0]), None);
+    }
+    #[cfg(feature = "cached")]
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, MAX_COL_NUM, false).unwrap();
+    sheet.with_prescan().unwrap();
+    let cached = sheet.into_cached_sheet().unwrap();
+    assert_eq!(cached.get_matched_rules("A1").unwrap().len(), 1);
+    assert!(cached.get_matched_rules("B1").unwrap().is_empty());
+    //  缓存范围不从A列开始
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 2, MAX_COL_NUM, false).unwrap();
+    sheet.with_prescan().unwrap();
+    let cached = sheet.into_cached_sheet().unwrap();
+    assert!(matches!(cached.get_cell_value("B1").unwrap(), CellValue::Number(v) if *v == 20.0));
+    assert!(matches!(cached.get_cell_value_with_merge_info("C1").unwrap().0, CellValue::Number(v) if *v == 300.0));
+    assert!(cached.get_matched_rules("B1").unwrap().is_empty());
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_sheet_layout(){
+    let sheet_xml = format!(
+        r#"<worksheet><sheetViews><sheetView workbookViewId="0"><pane ySplit="2" topLeftCell="A3" activePane="bottomLeft" state="frozen"/></sheetView></sheetViews><sheetFormatPr defaultRowHeight="15" baseColWidth="8"/><cols><col min="1" max="2" width="20.5" customWidth="1"/><col min="3" max="3" width="9" hidden="1"/></cols><sheetData><row r="1">{}</row><row r="2" ht="30" customHeight="1">{}{}</row><row r="3" hidden="1"><c r="A3"><v>1</v></c></row></sheetData><autoFilter ref="A2:B3"><filterColumn colId="0"><filters blank="1"><filter val="x"/><filter val="y"/></filters></filterColumn><filterColumn colId="1"><customFilters and="1"><customFilter operator="greaterThan" val="5"/><customFilter val="9"/></customFilters></filterColumn></autoFilter></worksheet>"#,
+        istr("A1", "title"), istr("A2", "a"), istr("B2", "b"),
+    );
+    let path = make_xlsx("layout", &sheet_xml);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, MAX_COL_NUM, false).unwrap();
+    let layout = sheet.get_layout().unwrap();
+    let pane = layout.pane.as_ref().unwrap();
+    assert_eq!((pane.frozen, pane.y_split, pane.top_left_cell.as_deref()), (true, 2.0, Some("A3")));
+    assert_eq!(layout.header_hint(), Some(2));
+    assert_eq!((layout.default_row_height, layout.default_col_width), (Some(15.0), None));
+    assert_eq!((layout.column_width(2), layout.column_width(4)), (Some(20.5), None));
+    assert!(layout.columns[1].hidden);
+    assert_eq!(layout.rows[&2].height, Some(30.0));
+    assert!(layout.rows[&3].hidden && !layout.rows.contains_key(&1));
+    let af = layout.auto_filter.as_ref().unwrap();
+    assert_eq!(af.range, "A2:B3");
+    assert_eq!((af.columns[0].values.clone(), af.columns[0].blank), (vec!["x".to_string(), "y".to_string()], true));
+    assert_eq!(af.columns[1].custom_filters, vec![("greaterThan".to_string(), "5".to_string()), ("equal".to_string(), "9".to_string())]);
+    assert!(af.columns[1].custom_and);
+    }
+    {
+    //  预扫描不读取行高，get_layout时补充
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 100, 0, 1, MAX_COL_NUM, false).unwrap();
+    assert!(sheet.with_prescan().unwrap().layout.rows.is_empty());
+    assert_eq!(sheet.get_layout().unwrap().rows.len(), 2);
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_sheet_images(){
+    let sheet_xml = r#"<worksheet><sheetData><row r="1"><c r="A1"><v>1</v></c></row><row r="3"><c r="B3" t="e" vm="1"><v>#VALUE!</v></c></row></sheetData><drawing r:id="rId1"/></worksheet>"#;
+    let drawing = r#"<xdr:wsDr xmlns:xdr="http://schemas.openxmlformats.org/drawingml/2006/spreadsheetDrawing" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><xdr:twoCellAnchor><xdr:from><xdr:col>1</xdr:col><xdr:colOff>0</xdr:colOff><xdr:row>4</xdr:row><xdr:rowOff>0</xdr:rowOff></xdr:from><xdr:to><xdr:col>3</xdr:col><xdr:colOff>0</xdr:colOff><xdr:row>9</xdr:row><xdr:rowOff>0</xdr:rowOff></xdr:to><xdr:pic><xdr:nvPicPr><xdr:cNvPr id="2" name="Picture 1" descr="crack"/></xdr:nvPicPr><xdr:blipFill><a:blip r:embed="rId1"/></xdr:blipFill><xdr:spPr><a:xfrm><a:ext cx="1" cy="1"/></a:xfrm></xdr:spPr></xdr:pic></xdr:twoCellAnchor><xdr:oneCellAnchor><xdr:from><xdr:col>0</xdr:col><xdr:colOff>0</xdr:colOff><xdr:row>0</xdr:row><xdr:rowOff>0</xdr:rowOff></xdr:from><xdr:ext cx="952500" cy="476250"/><xdr:pic><xdr:nvPicPr><xdr:cNvPr id="3" name="Picture 2"/></xdr:nvPicPr><xdr:blipFill><a:blip r:embed="rId2"/></xdr:blipFill></xdr:pic></xdr:oneCellAnchor></xdr:wsDr>"#;
+    let sheet_rels = r#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/drawing" Target="../drawings/drawing1.xml"/></Relationships>"#;
+    let drawing_rels = r#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="../media/image1.png"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="../media/image2.jpeg"/></Relationships>"#;
+    let types = r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="png" ContentType="image/png"/><Default Extension="jpeg" ContentType="image/jpeg"/></Types>"#;
+    let metadata = r#"<metadata xmlns:xlrd="http://schemas.microsoft.com/office/spreadsheetml/2017/richdata"><metadataTypes count="1"><metadataType name="XLRICHVALUE"/></metadataTypes><futureMetadata name="XLRICHVALUE" count="1"><bk><extLst><ext uri="{3e2802c4-a4d2-4d8b-9148-e3be6c30e623}"><xlrd:rvb i="0"/></ext></extLst></bk></futureMetadata><valueMetadata count="1"><bk><rc t="1" v="0"/></bk></valueMetadata></metadata>"#;
+    let structure = r#"<rvStructures count="1"><s t="_rvRel:LocalImage"><k n="_rvRel:LocalImageIdentifier" t="i"/><k n="CalcOrigin" t="i"/></s></rvStructures>"#;
+    let rich_value = r#"<rvData count="1"><rv s="0"><v>0</v><v>5</v></rv></rvData>"#;
+    let value_rel = r#"<richValueRels xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><rel r:id="rId1"/></richValueRels>"#;
+    let value_rel_rels = r#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="../media/image3.png"/></Relationships>"#;
+    let path = make_xlsx_with("images", sheet_xml, &[
+        ("[Content_Types].xml", types), ("xl/worksheets/_rels/sheet1.xml.rels", sheet_rels), ("xl/drawings/drawing1.xml", drawing), ("xl/drawings/_rels/drawing1.xml.rels", drawing_rels),
+        ("xl/media/image1.png", "png-1"), ("xl/media/image2.jpeg", "jpeg-2"), ("xl/media/image3.png", "png-3"),
+        ("xl/metadata.xml", metadata), ("xl/richData/rdrichvaluestructure.xml", structure), ("xl/richData/rdrichvalue.xml", rich_value), ("xl/richData/richValueRel.xml", value_rel), ("xl/richData/_rels/richValueRel.xml.rels", value_rel_rels),
+    ]);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let images = book.get_images("Sheet1").unwrap();
+    let found: Vec<_> = images.iter().map(|v| (v.part.as_str(), v.content_type.as_deref(), v.anchor, v.range)).collect();
+    assert_eq!(found, vec![
+        ("xl/media/image2.jpeg", Some("image/jpeg"), AnchorKind::OneCell, Some(((1, 1), (1, 1)))),
+        ("xl/media/image1.png", Some("image/png"), AnchorKind::TwoCell, Some(((5, 2), (10, 4)))),
+        ("xl/media/image3.png", Some("image/png"), AnchorKind::InCell, Some(((3, 2), (3, 2)))),
+    ]);
+    assert_eq!((images[0].size, images[1].descr.as_deref()), (Some((952500, 476250)), Some("crack")));
+    let mut bytes = Vec::new();
+    std::io::Read::read_to_end(&mut book.open_image(&images[1]).unwrap(), &mut bytes).unwrap();
+    assert_eq!(bytes, b"png-1");
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_sheet_charts(){
+    let sheet_rels = r#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/drawing" Target="/xl/drawings/drawing1.xml"/></Relationships>"#;
+    let drawing = r#"<xdr:wsDr xmlns:xdr="http://schemas.openxmlformats.org/drawingml/2006/spreadsheetDrawing" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:c="http://schemas.openxmlformats.org/drawingml/2006/chart" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><xdr:twoCellAnchor><xdr:from><xdr:col>3</xdr:col><xdr:colOff>0</xdr:colOff><xdr:row>1</xdr:row><xdr:rowOff>0</xdr:rowOff></xdr:from><xdr:to><xdr:col>8</xdr:col><xdr:colOff>0</xdr:colOff><xdr:row>15</xdr:row><xdr:rowOff>0</xdr:rowOff></xdr:to><xdr:graphicFrame macro=""><xdr:nvGraphicFramePr><xdr:cNvPr id="2" name="Chart 1"/><xdr:cNvGraphicFramePr/></xdr:nvGraphicFramePr><xdr:xfrm><a:off x="0" y="0"/><a:ext cx="0" cy="0"/></xdr:xfrm><a:graphic><a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/chart"><c:chart r:id="rId1"/></a:graphicData></a:graphic></xdr:graphicFrame><xdr:clientData/></xdr:twoCellAnchor></xdr:wsDr>"#;
+    let drawing_rels = r#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/chart" Target="../charts/chart1.xml"/></Relationships>"#;
+    let chart = r#"<c:chartSpace xmlns:c="http://schemas.openxmlformats.org/drawingml/2006/chart" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"><c:chart><c:title><c:tx><c:rich><a:p><a:r><a:t>Sales</a:t></a:r><a:r><a:t> 2024</a:t></a:r></a:p></c:rich></c:tx></c:title><c:plotArea><c:barChart><c:barDir val="col"/><c:ser><c:idx val="0"/><c:tx><c:strRef><c:f>Sheet1!$B$1</c:f><c:strCache><c:ptCount val="1"/><c:pt idx="0"><c:v>Q1</c:v></c:pt></c:strCache></c:strRef></c:tx><c:cat><c:strRef><c:f>Sheet1!$A$2:$A$4</c:f><c:strCache><c:ptCount val="3"/><c:pt idx="0"><c:v>east</c:v></c:pt><c:pt idx="2"><c:v>west</c:v></c:pt></c:strCache></c:strRef></c:cat><c:val><c:numRef><c:f>Sheet1!$B$2:$B$4</c:f><c:numCache><c:ptCount val="3"/><c:pt idx="0"><c:v>10</c:v></c:pt><c:pt idx="1"><c:v>2.5</c:v></c:pt><c:pt idx="4000000000"><c:v>9</c:v></c:pt></c:numCache></c:numRef></c:val></c:ser></c:barChart><c:lineChart><c:ser><c:tx><c:v>Target</c:v></c:tx><c:val><c:numRef><c:f>Sheet1!$C$2:$C$4</c:f></c:numRef></c:val></c:ser></c:lineChart><c:catAx><c:axId val="1"/></c:catAx><c:valAx><c:axId val="2"/><c:title><c:tx><c:rich><a:p><a:r><a:t>Amount</a:t></a:r></a:p></c:rich></c:tx></c:title></c:valAx></c:plotArea></c:chart></c:chartSpace>"#;
+    let path = make_xlsx_with("charts", "<worksheet><sheetData/></worksheet>", &[
+        ("xl/worksheets/_rels/sheet1.xml.rels", sheet_rels), ("xl/drawings/drawing1.xml", drawing), ("xl/drawings/_rels/drawing1.xml.rels", drawing_rels), ("xl/charts/chart1.xml", chart),
+    ]);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let charts = book.get_all_charts().unwrap().remove("Sheet1").unwrap();
+    assert_eq!(charts.len(), 1);
+    let chart = &charts[0];
+    assert_eq!((chart.part.as_str(), chart.name.as_deref(), chart.range), ("xl/charts/chart1.xml", Some("Chart 1"), Some(((2, 4), (16, 9)))));
+    assert_eq!(chart.chart_types, vec!["barChart".to_string(), "lineChart".to_string()]);
+    assert_eq!((chart.title.as_deref(), chart.axis_titles.clone()), (Some("Sales 2024"), vec!["Amount".to_string()]));
+    let bar = &chart.series[0];
+    assert_eq!((bar.name.as_deref(), bar.name_ref.as_deref(), bar.categories_ref.as_deref(), bar.values_ref.as_deref()), (Some("Q1"), Some("Sheet1!$B$1"), Some("Sheet1!$A$2:$A$4"), Some("Sheet1!$B$2:$B$4")));
+    assert_eq!(bar.categories, vec![Some("east".to_string()), None, Some("west".to_string())]);
+    assert_eq!(bar.values, vec![Some(10.0), Some(2.5), None]);
+    assert_eq!(book.get_warnings().len(), 1);
+    assert_eq!((chart.series[1].chart_type.as_str(), chart.series[1].name.as_deref(), chart.series[1].values.len()), ("lineChart", Some("Target"), 0));
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+/// compress data into the MS-OVBA container with literal tokens only
+#[cfg(feature = "vba")]
+fn ovba_compress(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![1];
+    for chunk in data.chunks(4096) {
+        let body: Vec<u8> = chunk.chunks(8).flat_map(|group| std::iter::once(0).chain(group.iter().copied())).collect();
+        out.extend((0xB000 | (body.len() as u16 - 1)).to_le_bytes());
+        out.extend(body);
+    }
+    out
+}
+
+#[test]
+pub fn test_vba_project(){
+    let path = make_xlsx("vba", "<worksheet><sheetData/></worksheet>");
+    assert!(!XlsxBook::new(&path, true).unwrap().has_macros());
+    #[cfg(feature = "vba")]
+    {
+    let record = |id: u16, data: &[u8]| -> Vec<u8> {
+        let mut rec = id.to_le_bytes().to_vec();
+        rec.extend((data.len() as u32).to_le_bytes());
+        rec.extend(data);
+        rec
+    };
+    let mut dir = Vec::new();
+    dir.extend(record(0x0001, &1u32.to_le_bytes()));
+    dir.extend(record(0x0003, &936u16.to_le_bytes()));
+    dir.extend(record(0x0004, b"VBAProject"));
+    dir.extend([0x09, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00]);
+    dir.extend(record(0x000F, &2u16.to_le_bytes()));
+    let modules: [(&[u8], &str, u16, &str); 2] = [(b"\xC4\xA3\xBF\xE91", "Module1", 0x0021, "Sub Hello()\r\n    MsgBox \"hi\"\r\nEnd Sub\r\n"), (b"ThisWorkbook", "ThisWorkbook", 0x0022, "Private Sub Workbook_Open()\r\nEnd Sub\r\n")];
+    let mut streams = Vec::new();
+    for (name, stream, kind, code) in modules {
+        dir.extend(record(0x0019, name));
+        dir.extend(record(0x001A, stream.as_bytes()));
+        dir.extend(record(0x0031, &3u32.to_le_bytes()));
+        dir.extend(record(kind, b""));
+        dir.extend(record(0x002B, b""));
+        let mut data = vec![0xCC; 3];    //  performance cache
+        data.extend(ovba_compress(code.as_bytes()));
+        streams.push((format!("/VBA/{}", stream), data));
+    }
+    dir.extend(record(0x0010, b""));
+    streams.push(("/VBA/dir".to_string(), ovba_compress(&dir)));
+    let build = |version: cfb::Version| {
+        let mut comp = cfb::CompoundFile::create_with_version(version, std::io::Cursor::new(Vec::new())).unwrap();
+        comp.create_storage("/VBA").unwrap();
+        for (stream, data) in streams.iter() {
+            comp.create_stream(stream).unwrap().write_all(data).unwrap();
+        }
+        comp.flush().unwrap();
+        comp.into_inner().into_inner()
+    };
+    let bin = build(cfb::Version::V4);
+    append_part(&path, "xl/vbaProject.bin", &bin);
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    assert!(book.has_macros());
+    let project = book.get_vba_project().unwrap().unwrap();
+    assert_eq!((project.name.as_str(), project.code_page), ("VBAProject", 936));
+    let found: Vec<_> = project.modules.iter().map(|m| (m.name.as_str(), m.stream_name.as_str(), m.kind)).collect();
+    assert_eq!(found, vec![("模块1", "Module1", VbaModuleKind::Procedural), ("ThisWorkbook", "ThisWorkbook", VbaModuleKind::Document)]);
+    assert_eq!(project.modules[0].code, modules[0].3);
+    //  修改目录项：版本3忽略大小的高32位，目录树成环时报错
+    let entry_pos = |bin: &[u8], name: &str| {
+        let name: Vec<u8> = name.encode_utf16().flat_map(|c| c.to_le_bytes()).collect();
+        (0..bin.len()).step_by(128).find(|&p| bin[p..].starts_with(&name) && bin[p+64] as usize == name.len() + 2).unwrap()
+    };
+    let with_bin = |name: &str, bin: &[u8]| {
+        let path = make_xlsx(name, "<worksheet><sheetData/></worksheet>");
+        append_part(&path, "xl/vbaProject.bin", bin);
+        path
+    };
+    let mut sized = build(cfb::Version::V3);
+    let p = entry_pos(&sized, "dir");
+    sized[p+124..p+128].copy_from_slice(&1u32.to_le_bytes());
+    let sized_path = with_bin("vba_sized", &sized);
+    assert_eq!(XlsxBook::new(&sized_path, true).unwrap().get_vba_project().unwrap().unwrap().modules.len(), 2);
+    let mut looped = bin.clone();
+    let (root, p) = (entry_pos(&looped, "Root Entry"), entry_pos(&looped, "VBA"));
+    let vba_id: [u8; 4] = looped[root+76..root+80].try_into().unwrap();
+    looped[p+76..p+80].copy_from_slice(&vba_id);
+    let looped_path = with_bin("vba_looped", &looped);
+    assert!(matches!(XlsxBook::new(&looped_path, true).unwrap().get_vba_project(), Err(XlsxError::MalformedVba(_))));
+    std::fs::remove_file(sized_path).unwrap();
+    std::fs::remove_file(looped_path).unwrap();
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_pivot_cache(){
+    let workbook = r#"<workbook xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="Sheet1" sheetId="1" r:id="rId1"/></sheets><pivotCaches><pivotCache cacheId="5" r:id="rId2"/></pivotCaches></workbook>"#;
+    let book_rels = r#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/pivotCacheDefinition" Target="pivotCache/pivotCacheDefinition1.xml"/></Relationships>"#;
+    let definition = r#"<pivotCacheDefinition xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" r:id="rId1" recordCount="3"><cacheSource type="worksheet"><worksheetSource ref="A1:C4" sheet="Data"/></cacheSource><cacheFields count="4"><cacheField name="Region" numFmtId="0"><sharedItems count="2"><s v="East"/><s v="West"/></sharedItems></cacheField><cacheField name="Amount" numFmtId="0"><sharedItems containsNumber="1" minValue="1" maxValue="9"/></cacheField><cacheField name="Day" numFmtId="14"><sharedItems containsDate="1" count="1"><d v="2024-01-02T00:00:00"/></sharedItems></cacheField><cacheField name="Double" formula="Amount*2" databaseField="0"/></cacheFields></pivotCacheDefinition>"#;
+    let definition_rels = r#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/pivotCacheRecords" Target="pivotCacheRecords1.xml"/></Relationships>"#;
+    let records = r#"<pivotCacheRecords count="3"><r><x v="0"/><n v="1.5"/><x v="0"/></r><r><x v="1"/><n v="9"/><m/></r><r><x v="0"/><b v="1"/><x v="0"/></r></pivotCacheRecords>"#;
+    let table = r#"<pivotTableDefinition name="PivotTable1" cacheId="5" dataCaption="Values"><location ref="E3:F6" firstHeaderRow="1" firstDataRow="1" firstDataCol="1"/><rowFields count="1"><field x="0"/></rowFields><colFields count="1"><field x="-2"/></colFields><pageFields count="1"><pageField fld="2" hier="-1"/></pageFields><dataFields count="1"><dataField name="Sum of Amount" fld="1" baseField="0" baseItem="0"/></dataFields></pivotTableDefinition>"#;
+    let table_rels = r#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/pivotCacheDefinition" Target="../pivotCache/pivotCacheDefinition1.xml"/></Relationships>"#;
+    let sheet_rels = r#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/pivotTable" Target="../pivotTables/pivotTable1.xml"/></Relationships>"#;
+    let path = make_xlsx_with("pivot", "<worksheet><sheetData/></worksheet>", &[
+        ("xl/pivotCache/pivotCacheDefinition1.xml", definition), ("xl/pivotCache/_rels/pivotCacheDefinition1.xml.rels", definition_rels), ("xl/pivotCache/pivotCacheRecords1.xml", records),
+        ("xl/pivotTables/pivotTable1.xml", table), ("xl/pivotTables/_rels/pivotTable1.xml.rels", table_rels), ("xl/worksheets/_rels/sheet1.xml.rels", sheet_rels),
+        ("xl/workbook.xml", workbook), ("xl/_rels/workbook.xml.rels", book_rels),
+    ]);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let caches = book.get_pivot_caches().unwrap();
+    assert_eq!(caches.len(), 1);
+    let cache = &caches[0];
+    assert_eq!((cache.cache_id, cache.source_sheet.as_deref(), cache.source_ref.as_deref(), cache.record_count), (Some(5), Some("Data"), Some("A1:C4"), Some(3)));
+    assert_eq!(cache.field_names(), vec!["Region", "Amount", "Day"]);
+    let batches: Vec<_> = book.get_pivot_records(cache, 2).unwrap().map(|v| v.unwrap()).collect();
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[1].0, vec![3]);
+    assert_eq!(format!("{:?}", batches[0].1), r#"[[String("East"), Number(1.5), Datetime(45293.0)], [String("West"), Number(9.0), Blank]]"#);
+    assert!(matches!(batches[1].1[0][1], CellValue::Bool(true)));
+    let tables = book.get_pivot_tables("Sheet1").unwrap();
+    let table = &tables[0];
+    assert_eq!((table.name.as_str(), table.cache_id, table.cache_part.as_deref(), table.location.as_str()), ("PivotTable1", Some(5), Some(cache.part.as_str()), "E3:F6"));
+    let names = |fields: &[i32]| fields.iter().map(|x| cache.field_name(*x).unwrap()).collect::<Vec<_>>();
+    assert_eq!((names(&table.row_fields), names(&table.col_fields), names(&table.page_fields)), (vec!["Region"], vec!["Values"], vec!["Day"]));
+    assert_eq!((table.data_fields[0].field, table.data_fields[0].subtotal.as_str()), (1, "sum"));
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_external_links(){
+    let workbook = r#"<workbook xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="Sheet1" sheetId="1" r:id="rId1"/></sheets><externalReferences><externalReference r:id="rId2"/></externalReferences></workbook>"#;
+    let book_rels = r#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/externalLink" Target="externalLinks/externalLink1.xml"/></Relationships>"#;
+    let link = r#"<externalLink xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><externalBook r:id="rId1"><sheetNames><sheetName val="Prices"/><sheetName val="Other"/></sheetNames><definedNames><definedName name="Rate" refersTo="=Prices!$B$1"/></definedNames><sheetDataSet><sheetData sheetId="0"><row r="1"><cell r="A1" t="s"><v>apple</v></cell><cell r="B1"><v>2.5</v></cell></row><row r="2"><cell r="A2" t="b"><v>1</v></cell><cell r="B2" t="e"><v>#REF!</v></cell></row></sheetData></sheetDataSet></externalBook></externalLink>"#;
+    let link_rels = r#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/externalLinkPath" Target="file:///C:\data\Price List.xlsx" TargetMode="External"/></Relationships>"#;
+    let path = make_xlsx_with("extlink", "<worksheet><sheetData/></worksheet>", &[
+        ("xl/externalLinks/externalLink1.xml", link), ("xl/externalLinks/_rels/externalLink1.xml.rels", link_rels),
+        ("xl/workbook.xml", workbook), ("xl/_rels/workbook.xml.rels", book_rels),
+    ]);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let links = book.get_external_links().unwrap();
+    assert_eq!(links.len(), 1);
+    let link = &links[0];
+    assert_eq!((link.index, link.part.as_str(), link.file_name()), (1, "xl/externalLinks/externalLink1.xml", Some("Price List.xlsx")));
+    assert_eq!(link.sheet_names, vec!["Prices", "Other"]);
+    assert_eq!(link.defined_names, vec![("Rate".to_string(), "=Prices!$B$1".to_string())]);
+    assert_eq!(link.sheets.len(), 1);
+    assert_eq!(format!("{:?}", link.get_cell_value("Prices", "A1").unwrap()), r#"Some(String("apple"))"#);
+    assert_eq!(format!("{:?}", link.get_cell_value("Prices", "$B$1").unwrap()), "Some(Number(2.5))");
+    assert!(matches!(link.get_cell_value("Prices", "A2").unwrap(), Some(CellValue::Bool(true))));
+    assert!(matches!(link.get_cell_value("Prices", "B2").unwrap(), Some(CellValue::Error(_))));
+    assert!(link.get_cell_value("Other", "A1").unwrap().is_none());
+    assert_eq!(resolve_external_refs("=[1]Prices!B1*2", &links), "='[Price List.xlsx]Prices'!B1*2");
+    assert_eq!(resolve_external_refs("=SUM('[1]Prices'!A1:A3)&\"[1]\"", &links), "=SUM('[Price List.xlsx]Prices'!A1:A3)&\"[1]\"");
+    assert_eq!(resolve_external_refs("=[2]Prices!A1+Table1[Col]", &links), "=[2]Prices!A1+Table1[Col]");
+    }
+    //  缺少关系的外部链接也占用序号，宽松模式下无法解析的缓存值保留为字符串
+    let workbook = workbook.replace(r#"<externalReference r:id="rId2"/>"#, r#"<externalReference r:id="rId9"/><externalReference r:id="rId2"/>"#);
+    let link = link.replace("<v>2.5</v>", "<v>n/a</v>");
+    let lenient_path = make_xlsx_with("extlink_lenient", "<worksheet><sheetData/></worksheet>", &[
+        ("xl/externalLinks/externalLink1.xml", &link), ("xl/externalLinks/_rels/externalLink1.xml.rels", link_rels),
+        ("xl/workbook.xml", &workbook), ("xl/_rels/workbook.xml.rels", book_rels),
+    ]);
+    {
+    assert!(XlsxBook::new(&lenient_path, true).unwrap().get_external_links().is_err());
+    let mut book = XlsxBook::new_with_options(&lenient_path, true, ReadOptions { strict: false, ..Default::default() }).unwrap();
+    let links = book.get_external_links().unwrap();
+    assert_eq!((links.len(), links[0].index), (1, 2));
+    assert_eq!(format!("{:?}", links[0].get_cell_value("Prices", "B1").unwrap()), r#"Some(String("n/a"))"#);
+    assert_eq!(book.get_warnings().len(), 2);
+    assert_eq!(resolve_external_refs("=[2]Prices!A1", &links), "='[Price List.xlsx]Prices'!A1");
+    }
+    std::fs::remove_file(lenient_path).unwrap();
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_protection(){
+    let workbook = r#"<workbook xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><workbookProtection workbookAlgorithmName="SHA-512" workbookHashValue="aGFzaA==" workbookSaltValue="c2FsdA==" workbookSpinCount="100000" lockStructure="1"></workbookProtection><sheets><sheet name="Sheet1" sheetId="1" r:id="rId1"/></sheets></workbook>"#;
+    let styles = r#"<styleSheet><cellStyleXfs count="1"><xf numFmtId="0"><protection locked="0"/></xf></cellStyleXfs><cellXfs count="3"><xf numFmtId="0"/><xf numFmtId="0" applyProtection="1"><protection locked="0"/></xf><xf numFmtId="0" applyProtection="1"><protection hidden="1"/></xf></cellXfs></styleSheet>"#;
+    let sheet = format!(r#"<worksheet><cols><col min="4" max="5" style="1"/><col min="6" max="6" style="0"/></cols><sheetData><row r="1">{}<c r="B1" s="1"/><c r="C1" s="2"><v>1</v></c></row><row r="2" s="1" customFormat="1"><c r="A2" s="1"><v>2</v></c></row><row r="3" s="1" customFormat="1"/><row r="4" s="1"/></sheetData><sheetProtection password="CC3D" sheet="1" objects="1" formatColumns="0" selectLockedCells="1"/><protectedRanges><protectedRange name="Input" sqref="A3:B4 D1" algorithmName="SHA-512" hashValue="aGFzaA==" saltValue="c2FsdA==" spinCount="100000"/></protectedRanges></worksheet>"#, istr("A1", "name"));
+    let path = make_xlsx_with("protection", &sheet, &[("xl/workbook.xml", workbook), ("xl/styles.xml", styles)]);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let wp = book.get_workbook_protection().unwrap();
+    assert!(wp.lock_structure && !wp.lock_windows);
+    let hash = wp.workbook_password.as_ref().unwrap();
+    assert_eq!((hash.algorithm_name.as_deref(), hash.spin_count, hash.legacy_hash.as_deref()), (Some("SHA-512"), Some(100000), None));
+    assert!(wp.revisions_password.is_none());
+    assert!(book.get_cell_protection(0).locked);
+    assert!(!book.get_cell_protection(1).locked);
+    assert!(book.get_cell_protection(2).locked && book.get_cell_protection(2).hidden);
+    assert!(book.get_cell_protection(9).locked);
+    let unlocked = book.get_unlocked_cells("Sheet1").unwrap();
+    assert_eq!((unlocked.cells, unlocked.columns, unlocked.rows), (vec![(1, 2), (2, 1)], vec![(4, 5)], vec![(2, 3)]));
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 10, 0, 1, 3, false).unwrap();
+    let sp = sheet.get_protection().unwrap().unwrap().clone();
+    assert!(sp.sheet && sp.objects && !sp.scenarios && sp.format_cells && !sp.format_columns && sp.select_locked_cells);
+    assert_eq!(sp.password.unwrap().legacy_hash.as_deref(), Some("CC3D"));
+    let ranges = sheet.get_protected_ranges().unwrap().clone();
+    assert_eq!((ranges.len(), ranges[0].name.as_str(), ranges[0].ranges.len()), (1, "Input", 2));
+    assert!(ranges[0].contains(4, 2) && ranges[0].contains(1, 4) && !ranges[0].contains(1, 1));
+    assert!(ranges[0].password.is_some());
+    }
+    #[cfg(feature = "cached")]
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 10, 0, 1, 3, false).unwrap();
+    sheet.with_prescan().unwrap();
+    let cached = sheet.into_cached_sheet().unwrap();
+    assert!(cached.get_protection().unwrap().sheet);
+    assert_eq!(cached.get_protected_ranges().unwrap().len(), 1);
+    }
+    let mut book = XlsxBook::new(make_xlsx_with("protection", "<worksheet><sheetData/></worksheet>", &[]), true).unwrap();
+    assert!(book.get_workbook_protection().is_none());
+    assert!(book.get_unlocked_cells("Sheet1").unwrap().is_empty());
+    let mut sheet = book.get_sheet_by_name(&"Sheet1".to_string(), 10, 0, 1, 3, false).unwrap();
+    assert!(sheet.get_protection().unwrap().is_none());
+    drop(sheet);
+    drop(book);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn test_strict_ooxml(){
+    let workbook = r#"<x:workbook xmlns:x="http://purl.oclc.org/ooxml/spreadsheetml/main" xmlns:rel="http://purl.oclc.org/ooxml/officeDocument/relationships"><x:sheets><x:sheet name="Data" sheetId="1" rel:id="rId1"></x:sheet><x:sheet name="Hidden" sheetId="2" state="hidden" rel:id="rId2"/></x:sheets></x:workbook>"#;
+    let book_rels = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://purl.oclc.org/ooxml/officeDocument/relationships/worksheet" Target="/xl/worksheets/sheet1.xml"/><Relationship Id="rId2" Type="http://purl.oclc.org/ooxml/officeDocument/relationships/worksheet" Target="worksheets/sheet2.xml"/></Relationships>"#;
+    let sheet = r#"<x:worksheet xmlns:x="http://purl.oclc.org/ooxml/spreadsheetml/main" xmlns:rel="http://purl.oclc.org/ooxml/officeDocument/relationships"><x:sheetData><x:row r="1"><x:c r="A1" t="d"><x:v>2024-01-02</x:v></x:c><x:c r="B1" t="d"><x:v>2024-01-02T12:00:00</x:v></x:c><x:c r="C1" t="d"><x:v>T06:00:00</x:v></x:c><x:c r="D1" t="d"><x:v>soon</x:v></x:c><x:c r="E1"><x:v>3</x:v></x:c><x:c r="F1" t="d"><x:v>2024-01-02T12:00:00+08:00</x:v></x:c><x:c r="G1" t="d"><x:v>T06:00:00-05:00</x:v></x:c></x:row></x:sheetData><x:hyperlinks><x:hyperlink ref="A1" rel:id="rId1"/></x:hyperlinks></x:worksheet>"#;
+    let sheet_rels = r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://purl.oclc.org/ooxml/officeDocument/relationships/hyperlink" Target="https://example.com" TargetMode="External"/></Relationships>"#;
+    let path = make_xlsx_with("strict", sheet, &[
+        ("xl/workbook.xml", workbook), ("xl/_rels/workbook.xml.rels", book_rels), ("xl/worksheets/_rels/sheet1.xml.rels", sheet_rels),
+        ("xl/worksheets/sheet2.xml", "<worksheet><sheetData/></worksheet>"),
+    ]);
+    {
+    let mut book = XlsxBook::new(&path, true).unwrap();
+    assert_eq!(book.get_visible_sheets(), &vec!["Data".to_string()]);
+    assert_eq!(book.get_hidden_sheets(), &vec!["Hidden".to_string()]);
+    assert_eq!(book.get_sheets_maps()["Data"], "xl/worksheets/sheet1.xml");
+    {
+    let mut sheet = book.get_sheet_by_name(&"Data".to_string(), 10, 0, 1, 7, false).unwrap();
+    let tail = sheet.with_prescan().unwrap();
+    assert_eq!(tail.hyperlinks[0].target.as_deref(), Some("https://example.com"));
+    let (_, rows) = sheet.get_remaining_cells().unwrap().unwrap();
+    assert_eq!(format!("{:?}", rows[0]), r#"[Date(45293.0), Datetime(45293.5), Time(0.25), String("soon"), Number(3.0), Datetime(45293.5), Time(0.25)]"#);
+    }
+    let mut sheet = book.get_sheet_by_name(&"Data".to_string(), 10, 0, 1, 5, false).unwrap();
+    sheet.with_parallel(2).unwrap();
+    let (_, rows) = sheet.get_remaining_cells().unwrap().unwrap();
+    assert!(matches!(rows[0][0], CellValue::Date(_)));
+    }
+    std::fs::remove_file(path).unwrap();
+}